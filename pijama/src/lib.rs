@@ -8,13 +8,50 @@ use codespan_reporting::{
 };
 use structopt::StructOpt;
 
-use pijama_driver::LangError;
+use pijama_driver::{LangError, Warning};
+use pijama_parser::ParsingErrorKind;
 
 #[derive(Debug, StructOpt)]
 #[structopt(name = "pijama", about = "The Pijama compiler")]
 pub struct Options {
     #[structopt(name = "INPUT", help = "Path to the input file.")]
     pub path: String,
+    #[structopt(
+        long = "--emit-tokens",
+        help = "Print the tokens of the input and exit"
+    )]
+    pub emit_tokens: bool,
+    #[structopt(
+        long = "--check-only",
+        help = "Check the input for errors without running it"
+    )]
+    pub check_only: bool,
+    #[structopt(long = "--warn", help = "Report code that is probably a mistake")]
+    pub warn: bool,
+    #[structopt(long = "--strict-types", help = "Reject programs with ambiguous types")]
+    pub strict_types: bool,
+    #[structopt(
+        long = "--print-types",
+        help = "Annotate printed values with their type"
+    )]
+    pub print_types: bool,
+    #[structopt(long = "--inline", help = "Inline calls to small functions")]
+    pub inline: bool,
+    #[structopt(
+        long = "--simplify",
+        help = "Remove the branches of conditionals that are never taken"
+    )]
+    pub simplify: bool,
+    #[structopt(
+        long = "--cse",
+        help = "Evaluate repeated pure subexpressions only once"
+    )]
+    pub cse: bool,
+    #[structopt(
+        long = "--explicit-rec",
+        help = "Only functions marked with `rec` can be recursive"
+    )]
+    pub explicit_rec: bool,
     #[structopt(flatten)]
     pub machine_opts: MachineOptions,
 }
@@ -23,10 +60,31 @@ pub struct Options {
 pub struct MachineOptions {
     #[structopt(
         long = "--overflow-check",
-        help = "Execution panics on integer overflow"
+        help = "Report integer overflow as a runtime error"
     )]
     // If the flag is not passed, the default value is `false`.
     pub overflow_check: bool,
+    #[structopt(
+        long = "--no-overflow-checks",
+        help = "Integer operations wrap around silently on overflow",
+        conflicts_with = "overflow-check"
+    )]
+    pub no_overflow_checks: bool,
+    #[structopt(
+        long = "--max-output-bytes",
+        help = "Stop the execution if it prints more than this number of bytes"
+    )]
+    pub max_output_bytes: Option<usize>,
+    #[structopt(
+        long = "--eager",
+        help = "Evaluate the arguments of functions before calling them"
+    )]
+    pub eager: bool,
+    #[structopt(
+        long = "--max-steps",
+        help = "Stop the execution if it takes more than this number of steps"
+    )]
+    pub max_steps: Option<usize>,
 }
 
 pub fn display_error(input: &str, path: &str, error: &LangError) {
@@ -36,17 +94,68 @@ pub fn display_error(input: &str, path: &str, error: &LangError) {
 
     let file_id = files.add(path, input);
 
-    let (msg, loc) = match &error {
-        LangError::Ty(error) => ("Type error", error.loc()),
-        LangError::Parse(error) => ("Parsing error", error.loc()),
-        LangError::Lower(error) => ("Lowering error", error.loc()),
+    let msg = match &error {
+        LangError::Ty(_) => "Type error",
+        LangError::Parse(_) => "Parsing error",
+        LangError::Lower(_) => "Lowering error",
+        LangError::Resolve(_) => "Name resolution error",
+        LangError::Include(_) => "Include error",
+        LangError::Runtime(_) => "Runtime error",
+    };
+    let loc = error.loc();
+    let mut labels =
+        vec![Label::primary(file_id, loc.start..loc.end).with_message(error.to_string())];
+
+    if let LangError::Parse(error) = error {
+        if let ParsingErrorKind::MissingEnd { opened } = error.kind() {
+            labels.push(
+                Label::secondary(file_id, opened.start..opened.end)
+                    .with_message("Block opened here"),
+            );
+        }
+    }
+
+    let diagnostic = Diagnostic::error().with_message(msg).with_labels(labels);
+
+    emit(&mut writer.lock(), &config, &files, &diagnostic).unwrap();
+}
+
+pub fn display_warning(input: &str, path: &str, warning: &Warning) {
+    let writer = StandardStream::stderr(ColorChoice::Always);
+    let config = codespan_reporting::term::Config::default();
+    let mut files = SimpleFiles::new();
+
+    let file_id = files.add(path, input);
+
+    let loc = warning.loc();
+    let mut labels =
+        vec![Label::primary(file_id, loc.start..loc.end).with_message(warning.to_string())];
+
+    let msg = match warning {
+        Warning::Shadowed { outer_loc, .. } => {
+            labels.push(
+                Label::secondary(file_id, outer_loc.start..outer_loc.end)
+                    .with_message("Previous binding"),
+            );
+            "Shadowed binding"
+        }
+        Warning::DuplicateBinding { first_loc, .. } => {
+            labels.push(
+                Label::secondary(file_id, first_loc.start..first_loc.end)
+                    .with_message("First binding"),
+            );
+            "Duplicate binding"
+        }
+        Warning::UnreachableBranch { cond_loc, .. } => {
+            labels.push(
+                Label::secondary(file_id, cond_loc.start..cond_loc.end)
+                    .with_message("This condition negates a previous one"),
+            );
+            "Unreachable branch"
+        }
     };
 
-    let diagnostic = Diagnostic::error()
-        .with_message(msg)
-        .with_labels(vec![
-            Label::primary(file_id, loc.start..loc.end).with_message(error.to_string())
-        ]);
+    let diagnostic = Diagnostic::warning().with_message(msg).with_labels(labels);
 
     emit(&mut writer.lock(), &config, &files, &diagnostic).unwrap();
 }
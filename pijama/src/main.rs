@@ -1,9 +1,10 @@
 use structopt::StructOpt;
 
-use std::fs::read_to_string;
+use std::{fs::read_to_string, process};
 
-use pijama::{display_error, Options};
-use pijama_driver::run;
+use pijama::{display_error, display_warning, Options};
+use pijama_driver::{check, run, warnings, Config, LangError};
+use pijama_parser::tokenize;
 
 fn main() {
     let options = Options::from_args();
@@ -16,7 +17,54 @@ fn main() {
         }
     };
 
-    match run(&input, options.machine_opts.overflow_check) {
+    if options.emit_tokens {
+        match tokenize(&input) {
+            Ok(tokens) => {
+                for token in tokens {
+                    println!(
+                        "{}..{} {:?} {}",
+                        token.loc.start,
+                        token.loc.end,
+                        token.content.kind(),
+                        token.content
+                    );
+                }
+            }
+            Err(err) => display_error(&input, &options.path, &LangError::Parse(err)),
+        }
+        return;
+    }
+
+    if options.warn {
+        // Errors are reported afterwards, when the input is checked or run.
+        for warning in warnings(&input).unwrap_or_default() {
+            display_warning(&input, &options.path, &warning);
+        }
+    }
+
+    let config = Config {
+        overflow_check: options.machine_opts.overflow_check,
+        wrapping: options.machine_opts.no_overflow_checks,
+        strict_types: options.strict_types,
+        print_types: options.print_types,
+        inline: options.inline,
+        simplify: options.simplify,
+        cse: options.cse,
+        explicit_rec: options.explicit_rec,
+        max_output_bytes: options.machine_opts.max_output_bytes,
+        eager: options.machine_opts.eager,
+        max_steps: options.machine_opts.max_steps,
+    };
+
+    if options.check_only {
+        if let Err(err) = check(&input, config) {
+            display_error(&input, &options.path, &err);
+            process::exit(1);
+        }
+        return;
+    }
+
+    match run(&input, config) {
         Ok(()) => (),
         Err(err) => display_error(&input, &options.path, &err),
     }
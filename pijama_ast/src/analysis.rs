@@ -1,13 +1,198 @@
 //! An assortment of checks that are done before lowering.
+use std::fmt;
+
 use crate::{
-    location::Located,
-    node::{Block, Expression, Name},
+    location::{Located, Location},
+    node::{Block, Branch, Expression, Name, Node, Param, Primitive, Statement, UnOp},
     ty::TyAnnotation,
     visitor::NodeVisitor,
 };
 
+/// A warning about code that is valid but that is probably a mistake.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Warning {
+    /// A binding shadows another binding with the same name done in an outer scope.
+    Shadowed {
+        /// The name being shadowed.
+        name: String,
+        /// Location of the name in the inner binding.
+        inner_loc: Location,
+        /// Location of the name in the outer binding.
+        outer_loc: Location,
+    },
+    /// A binding shadows another binding with the same name done in the same scope.
+    DuplicateBinding {
+        /// The name being bound twice.
+        name: String,
+        /// Location of the name in the first binding.
+        first_loc: Location,
+        /// Location of the name in the second binding.
+        second_loc: Location,
+    },
+    /// A branch of a conditional can never be taken because one of the previous conditions is
+    /// the negation of another previous condition.
+    UnreachableBranch {
+        /// Location of the first part of the conditional that can never be evaluated.
+        loc: Location,
+        /// Location of the condition that negates a previous one.
+        cond_loc: Location,
+    },
+}
+
+impl Warning {
+    /// Returns the location of the code causing the warning.
+    pub fn loc(&self) -> Location {
+        match self {
+            Warning::Shadowed { inner_loc, .. } => *inner_loc,
+            Warning::DuplicateBinding { second_loc, .. } => *second_loc,
+            Warning::UnreachableBranch { loc, .. } => *loc,
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Warning::Shadowed { name, .. } => {
+                write!(f, "Binding shadows the name `{}` of an outer scope", name)
+            }
+            Warning::DuplicateBinding { name, .. } => {
+                write!(f, "Name `{}` is already bound in this scope", name)
+            }
+            Warning::UnreachableBranch { .. } => write!(f, "This branch is unreachable"),
+        }
+    }
+}
+
 /// Checks if a function is recursive or not.
 pub fn is_fn_def_recursive<'a>(name: Name<'a>, body: &Block<'a>) -> bool {
+    references(name, body)
+}
+
+/// Groups the function definitions done directly inside a block into mutually recursive groups.
+///
+/// Each group is a strongly connected component of the graph whose vertices are the functions
+/// defined in the block and where there is an edge from `f` to `g` if `g` is referenced inside
+/// the body of `f`. The groups are returned in such a way that every group only references
+/// functions in itself or in the groups before it. The names inside each group follow the order of
+/// the definitions.
+pub fn recursive_groups<'a>(block: &Block<'a>) -> Vec<Vec<Name<'a>>> {
+    let defs = block
+        .nodes
+        .iter()
+        .filter_map(|node| match node {
+            Node::Stat(Located {
+                content: Statement::FnDef(name, params, body, _),
+                ..
+            }) => Some((name.content, params, &body.item)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let edges = defs
+        .iter()
+        .map(|(_, params, body)| {
+            defs.iter()
+                .enumerate()
+                .filter(|(_, (target, _, _))| {
+                    // The arguments of a function shadow any other function with the same name.
+                    !params.iter().any(|param| param.ann.item.content == *target)
+                        && references(*target, body)
+                })
+                .map(|(index, _)| index)
+                .collect()
+        })
+        .collect();
+
+    let mut finder = ComponentFinder::new(edges);
+    for vertex in 0..defs.len() {
+        if finder.indices[vertex].is_none() {
+            finder.connect(vertex);
+        }
+    }
+
+    finder
+        .components
+        .into_iter()
+        .map(|mut component| {
+            component.sort_unstable();
+            component.into_iter().map(|index| defs[index].0).collect()
+        })
+        .collect()
+}
+
+/// Finds the strongly connected components of a graph using Tarjan's algorithm.
+struct ComponentFinder {
+    /// Adjacency list of the graph.
+    edges: Vec<Vec<usize>>,
+    /// Order in which each vertex was visited, if it has been visited already.
+    indices: Vec<Option<usize>>,
+    /// Smallest index reachable from each vertex.
+    lowlinks: Vec<usize>,
+    /// Vertices that are still waiting to be assigned to a component.
+    stack: Vec<usize>,
+    /// Stores if each vertex is in the stack.
+    on_stack: Vec<bool>,
+    /// Number of visited vertices.
+    count: usize,
+    /// Components found so far, in reverse topological order.
+    components: Vec<Vec<usize>>,
+}
+
+impl ComponentFinder {
+    fn new(edges: Vec<Vec<usize>>) -> Self {
+        let len = edges.len();
+        ComponentFinder {
+            edges,
+            indices: vec![None; len],
+            lowlinks: vec![0; len],
+            stack: Vec::with_capacity(len),
+            on_stack: vec![false; len],
+            count: 0,
+            components: Vec::new(),
+        }
+    }
+
+    /// Visits a vertex and all the vertices reachable from it.
+    fn connect(&mut self, vertex: usize) {
+        self.indices[vertex] = Some(self.count);
+        self.lowlinks[vertex] = self.count;
+        self.count += 1;
+        self.stack.push(vertex);
+        self.on_stack[vertex] = true;
+
+        for i in 0..self.edges[vertex].len() {
+            let target = self.edges[vertex][i];
+            match self.indices[target] {
+                None => {
+                    self.connect(target);
+                    self.lowlinks[vertex] = self.lowlinks[vertex].min(self.lowlinks[target]);
+                }
+                Some(index) if self.on_stack[target] => {
+                    self.lowlinks[vertex] = self.lowlinks[vertex].min(index);
+                }
+                Some(_) => (),
+            }
+        }
+
+        // If this vertex is the root of a component, pop the whole component from the stack.
+        if Some(self.lowlinks[vertex]) == self.indices[vertex] {
+            let mut component = Vec::new();
+            loop {
+                let member = self.stack.pop().expect("the stack is empty");
+                self.on_stack[member] = false;
+                component.push(member);
+                if member == vertex {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
+
+/// Checks if a name is referenced inside a block without being shadowed.
+fn references<'a>(name: Name<'a>, body: &Block<'a>) -> bool {
     let mut checker = RecursionChecker {
         name,
         is_rec: false,
@@ -85,7 +270,7 @@ impl<'a> NodeVisitor<'a> for RecursionChecker<'a> {
     fn visit_fn_def(
         &mut self,
         name: &Located<Name<'a>>,
-        args: &[TyAnnotation<Located<Name<'a>>>],
+        params: &[Param<'a>],
         body: &TyAnnotation<Block<'a>>,
     ) {
         if name.content == self.name {
@@ -93,17 +278,17 @@ impl<'a> NodeVisitor<'a> for RecursionChecker<'a> {
             // the current scope.
             self.is_shadowed = true;
         } else {
-            for arg in args {
+            for param in params {
                 // If any of the arguments uses the same name as the target, the latter is being
                 // shadowed in the current scope.
-                if arg.item.content == self.name {
+                if param.ann.item.content == self.name {
                     self.is_shadowed = true;
                     break;
                 }
             }
         }
         // Keep visiting
-        self.super_fn_def(name, args, body);
+        self.super_fn_def(name, params, body);
     }
 
     fn visit_anon_fn(
@@ -134,3 +319,207 @@ impl<'a> NodeVisitor<'a> for RecursionChecker<'a> {
         self.pop_scope();
     }
 }
+
+/// Finds the bindings inside a block that shadow a binding done in an outer scope.
+///
+/// Only assignments and function definitions are reported, but they can shadow any binding,
+/// including the parameters of a function. Bindings that shadow another binding in the same
+/// scope are not reported.
+pub fn shadowed_bindings<'a>(block: &Block<'a>) -> Vec<Warning> {
+    binding_warnings(block)
+        .into_iter()
+        .filter(|warning| matches!(warning, Warning::Shadowed { .. }))
+        .collect()
+}
+
+/// Finds the bindings inside a block that shadow a binding done in the same scope.
+///
+/// Only assignments and function definitions are reported.
+pub fn duplicate_bindings<'a>(block: &Block<'a>) -> Vec<Warning> {
+    binding_warnings(block)
+        .into_iter()
+        .filter(|warning| matches!(warning, Warning::DuplicateBinding { .. }))
+        .collect()
+}
+
+/// Finds the bindings inside a block that shadow another binding, either in an outer scope or in
+/// the same scope. The warnings are returned in the same order as the bindings.
+pub fn binding_warnings<'a>(block: &Block<'a>) -> Vec<Warning> {
+    let mut checker = BindingChecker::default();
+    checker.visit_block(block);
+    checker.warnings
+}
+
+/// Visitor that finds bindings shadowing other bindings.
+#[derive(Default)]
+struct BindingChecker<'a> {
+    /// The names bound in each scope, the last one being the current scope.
+    scopes: Vec<Vec<Located<Name<'a>>>>,
+    /// The warnings found so far.
+    warnings: Vec<Warning>,
+}
+
+impl<'a> BindingChecker<'a> {
+    /// Binds a name in the current scope, checking if it shadows a name in the current scope or in
+    /// an outer scope.
+    fn bind(&mut self, name: &Located<Name<'a>>) {
+        let (current, outer) = self
+            .scopes
+            .split_last_mut()
+            .expect("there are no scopes in the stack");
+
+        if let Some(first) = current
+            .iter()
+            .rev()
+            .find(|bound| bound.content == name.content)
+        {
+            self.warnings.push(Warning::DuplicateBinding {
+                name: name.content.0.to_owned(),
+                first_loc: first.loc,
+                second_loc: name.loc,
+            });
+        } else {
+            let shadowed = outer.iter().rev().find_map(|scope| {
+                scope
+                    .iter()
+                    .rev()
+                    .find(|bound| bound.content == name.content)
+            });
+
+            if let Some(shadowed) = shadowed {
+                self.warnings.push(Warning::Shadowed {
+                    name: name.content.0.to_owned(),
+                    inner_loc: name.loc,
+                    outer_loc: shadowed.loc,
+                });
+            }
+        }
+
+        current.push(*name);
+    }
+}
+
+impl<'a> NodeVisitor<'a> for BindingChecker<'a> {
+    fn visit_assign(
+        &mut self,
+        annotation: &TyAnnotation<Located<Name<'a>>>,
+        expr: &Located<Expression<'a>>,
+    ) {
+        // The name is not in scope inside its own definition.
+        self.super_assign(annotation, expr);
+        self.bind(&annotation.item);
+    }
+
+    fn visit_fn_def(
+        &mut self,
+        name: &Located<Name<'a>>,
+        params: &[Param<'a>],
+        body: &TyAnnotation<Block<'a>>,
+    ) {
+        self.bind(name);
+        // The parameters live in their own scope, between the function and its body.
+        self.scopes
+            .push(params.iter().map(|param| param.ann.item).collect());
+        self.super_fn_def(name, params, body);
+        self.scopes.pop();
+    }
+
+    fn visit_anon_fn(
+        &mut self,
+        args: &[TyAnnotation<Located<Name<'a>>>],
+        body: &TyAnnotation<Block<'a>>,
+    ) {
+        self.scopes.push(args.iter().map(|arg| arg.item).collect());
+        self.super_anon_fn(args, body);
+        self.scopes.pop();
+    }
+
+    fn visit_block(&mut self, block: &Block<'a>) {
+        self.scopes.push(Vec::new());
+        self.super_block(block);
+        self.scopes.pop();
+    }
+}
+
+/// Finds the branches of conditionals that can never be taken.
+///
+/// Only the simplest case is detected: a condition that is the negation of a previous condition
+/// in the same conditional, like `if b do ... elif !b do ... else ... end`. The branches after
+/// such condition are unreachable.
+pub fn unreachable_branches<'a>(block: &Block<'a>) -> Vec<Warning> {
+    let mut checker = BranchChecker::default();
+    checker.visit_block(block);
+    checker.warnings
+}
+
+/// Runs all the analyses that return warnings over a block.
+pub fn all_warnings<'a>(block: &Block<'a>) -> Vec<Warning> {
+    let mut warnings = binding_warnings(block);
+    warnings.extend(unreachable_branches(block));
+    warnings
+}
+
+/// Visitor that finds unreachable branches.
+#[derive(Default)]
+struct BranchChecker {
+    /// The warnings found so far.
+    warnings: Vec<Warning>,
+}
+
+impl<'a> NodeVisitor<'a> for BranchChecker {
+    fn visit_cond(&mut self, if_branch: &Branch<'a>, branches: &[Branch<'a>], el_blk: &Block<'a>) {
+        let conds = Some(if_branch)
+            .into_iter()
+            .chain(branches)
+            .map(|branch| &branch.cond)
+            .collect::<Vec<_>>();
+
+        let negated = conds.iter().enumerate().skip(1).find(|(i, cond)| {
+            conds[..*i]
+                .iter()
+                .any(|previous| is_negation(previous, cond) || is_negation(cond, previous))
+        });
+
+        if let Some((i, cond)) = negated {
+            // The first unreachable part is the next branch or the `else` block.
+            let loc = match conds.get(i + 1) {
+                Some(next) => block_loc(next),
+                None => block_loc(el_blk),
+            };
+            self.warnings.push(Warning::UnreachableBranch {
+                loc,
+                cond_loc: block_loc(cond),
+            });
+        }
+
+        self.super_cond(if_branch, branches, el_blk);
+    }
+}
+
+/// Checks if the block `negated` is the logical negation of the block `cond`.
+///
+/// Only blocks consisting of a single expression are compared, so no binding inside the blocks
+/// can change the meaning of the names in them.
+fn is_negation<'a>(cond: &Block<'a>, negated: &Block<'a>) -> bool {
+    if !cond.nodes.is_empty() || !negated.nodes.is_empty() {
+        return false;
+    }
+
+    match &negated.expr.content {
+        Expression::UnaryOp(UnOp::Not, expr) => *expr == cond.expr,
+        Expression::Call(func, args) => {
+            func.content == Expression::PrimFn(Primitive::Not)
+                && args.len() == 1
+                && args[0] == *cond.expr
+        }
+        _ => false,
+    }
+}
+
+/// Returns the location of a block, from its first node to its final expression.
+fn block_loc(block: &Block<'_>) -> Location {
+    match block.nodes.front() {
+        Some(node) => node.loc() + block.expr.loc,
+        None => block.expr.loc,
+    }
+}
@@ -4,18 +4,37 @@ use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
 /// Represents a location in the source code file.
 ///
 /// Both the start and end correspond to locations reported by `nom_locate`.
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Location {
     /// Start of the location.
     pub start: usize,
     /// End of the location.
     pub end: usize,
+    /// Index of the file of the location.
+    ///
+    /// Programs made of a single source use the file `0`.
+    pub file: usize,
 }
 
 impl Location {
-    /// Constructs a new `Location` instance.
+    /// Constructs a new `Location` instance in the file `0`.
     pub const fn new(start: usize, end: usize) -> Self {
-        Location { start, end }
+        Location {
+            start,
+            end,
+            file: 0,
+        }
+    }
+    /// Returns the same location inside the file with index `file`.
+    pub const fn in_file(self, file: usize) -> Self {
+        Location { file, ..self }
+    }
+    /// Returns `true` if `offset` is inside this `Location`.
+    ///
+    /// The end of a location is exclusive, so empty locations do not contain any offset.
+    pub const fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset < self.end
     }
     /// Creates a new `Located` consuming this `Location`.
     pub fn with_content<T>(self, content: T) -> Located<T> {
@@ -24,10 +43,16 @@ impl Location {
 }
 
 /// Adding two locations `l1` and `l2` returns a location starting in `l1.start` and ending in `l2.end`.
+///
+/// Locations in different files cannot be joined, so adding them returns `l1`. This happens when
+/// a program with include directives joins a node of an included file with a node of the file
+/// including it, e.g. when lowering the block made of both files.
 impl std::ops::Add for Location {
     type Output = Self;
     fn add(mut self, other: Self) -> Self {
-        self.end = other.end;
+        if self.file == other.file {
+            self.end = other.end;
+        }
         self
     }
 }
@@ -35,6 +60,7 @@ impl std::ops::Add for Location {
 /// Wrapper type with a `Location` field.
 ///
 /// It is used to add a location to elements in the AST and intermediate representations.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Located<T> {
     /// Content of the wrapper.
     pub content: T,
@@ -44,11 +70,17 @@ pub struct Located<T> {
 
 impl<T> Located<T> {
     /// Creates a new `Located`.
+    ///
+    /// In debug builds, this function panics if the location ends before it starts.
     pub fn new(content: T, loc: impl Into<Location>) -> Self {
-        Located {
-            content,
-            loc: loc.into(),
-        }
+        let loc = loc.into();
+        debug_assert!(
+            loc.start <= loc.end,
+            "Location {}..{} ends before it starts",
+            loc.start,
+            loc.end
+        );
+        Located { content, loc }
     }
     /// Maps the content of the `Located` leaving its location untouched.
     pub fn map<U: Debug, F: FnOnce(T) -> U>(self, f: F) -> Located<U> {
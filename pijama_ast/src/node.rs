@@ -14,6 +14,11 @@ use crate::{
 
 /// A block is a sequence of nodes terminating in an expression.
 #[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(deserialize = "'de: 'a"))
+)]
 pub struct Block<'a> {
     /// Nodes of the block.
     pub nodes: VecDeque<Node<'a>>,
@@ -21,9 +26,26 @@ pub struct Block<'a> {
     pub expr: Box<Located<Expression<'a>>>,
 }
 
+impl<'a> Block<'a> {
+    /// Returns the innermost expression inside the block whose location contains `offset`.
+    ///
+    /// This is useful to find the expression under the cursor of an editor.
+    pub fn node_at(&self, offset: usize) -> Option<&Located<Expression<'a>>> {
+        self.nodes
+            .iter()
+            .find_map(|node| node.node_at(offset))
+            .or_else(|| expr_at(&self.expr, offset))
+    }
+}
+
 /// A Node in the AST that encapsulates the different expressions and statements that Pijama's
 /// syntax supports.
 #[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(deserialize = "'de: 'a"))
+)]
 pub enum Node<'a> {
     /// A statement.
     Stat(Located<Statement<'a>>),
@@ -38,23 +60,62 @@ impl<'a> Node<'a> {
             Node::Stat(Located { loc, .. }) | Node::Expr(Located { loc, .. }) => loc,
         }
     }
+
+    /// Returns the innermost expression inside the node whose location contains `offset`.
+    pub fn node_at(&self, offset: usize) -> Option<&Located<Expression<'a>>> {
+        match self {
+            Node::Expr(expr) => expr_at(expr, offset),
+            Node::Stat(stat) if stat.loc.contains(offset) => match &stat.content {
+                Statement::Assign(_, expr) => expr_at(expr, offset),
+                Statement::FnDef(_, _, body, _) => body.item.node_at(offset),
+            },
+            Node::Stat(_) => None,
+        }
+    }
 }
 
 /// An AST node that performs an action.
 #[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(deserialize = "'de: 'a"))
+)]
 pub enum Statement<'a> {
     /// Statement containing an assignment.
     Assign(TyAnnotation<Located<Name<'a>>>, Located<Expression<'a>>),
     /// Statement containing a function definition.
+    ///
+    /// The last field is `true` if the function was explicitly marked as recursive using `rec`.
     FnDef(
         Located<Name<'a>>,
-        Vec<TyAnnotation<Located<Name<'a>>>>,
+        Vec<Param<'a>>,
         TyAnnotation<Block<'a>>,
+        bool,
     ),
 }
 
+/// A parameter of a function definition.
+#[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(deserialize = "'de: 'a"))
+)]
+pub struct Param<'a> {
+    /// The name of the parameter and its type annotation.
+    pub ann: TyAnnotation<Located<Name<'a>>>,
+    /// The value used for this parameter when it is missing in a call.
+    pub default: Option<Located<Literal>>,
+}
+
 /// An AST node that produces a value.
 #[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(deserialize = "'de: 'a"))
+)]
 pub enum Expression<'a> {
     /// Expression containing a binary operation.
     BinaryOp(
@@ -62,6 +123,14 @@ pub enum Expression<'a> {
         Box<Located<Expression<'a>>>,
         Box<Located<Expression<'a>>>,
     ),
+    /// Expression containing two or more chained ordering comparisons, like `a < b <= c`.
+    ///
+    /// Each comparison is done between an operand and the next one, and the chain is true only if
+    /// all of them are true.
+    Chain(
+        Box<Located<Expression<'a>>>,
+        Vec<(BinOp, Located<Expression<'a>>)>,
+    ),
     /// Expression containing a unary operation.
     UnaryOp(UnOp, Box<Located<Expression<'a>>>),
     /// Expression containing a conditional.
@@ -79,11 +148,55 @@ pub enum Expression<'a> {
     Name(Name<'a>),
     /// Expression containing a primitive function.
     PrimFn(Primitive),
+    /// Expression containing a sequence of nodes.
+    Block(Block<'a>),
+}
+
+/// Returns `expr` or the innermost expression inside it whose location contains `offset`.
+fn expr_at<'a, 'b>(
+    expr: &'b Located<Expression<'a>>,
+    offset: usize,
+) -> Option<&'b Located<Expression<'a>>> {
+    if !expr.loc.contains(offset) {
+        return None;
+    }
+
+    let inner = match &expr.content {
+        Expression::BinaryOp(_, expr1, expr2) => {
+            expr_at(expr1, offset).or_else(|| expr_at(expr2, offset))
+        }
+        Expression::Chain(first, rest) => std::iter::once(first.as_ref())
+            .chain(rest.iter().map(|(_, expr)| expr))
+            .find_map(|expr| expr_at(expr, offset)),
+        Expression::UnaryOp(_, expr) => expr_at(expr, offset),
+        Expression::Cond(if_branch, branches, el_blk) => std::iter::once(if_branch)
+            .chain(branches)
+            .find_map(|branch| {
+                branch
+                    .cond
+                    .node_at(offset)
+                    .or_else(|| branch.body.node_at(offset))
+            })
+            .or_else(|| el_blk.node_at(offset)),
+        Expression::AnonFn(_, body) => body.item.node_at(offset),
+        Expression::Call(func, args) => std::iter::once(func.as_ref())
+            .chain(args)
+            .find_map(|expr| expr_at(expr, offset)),
+        Expression::Block(block) => block.node_at(offset),
+        Expression::Literal(_) | Expression::Name(_) | Expression::PrimFn(_) => None,
+    };
+
+    Some(inner.unwrap_or(expr))
 }
 
 /// Encapsulates a conditional statement in Pijama's syntax. It is used to represent both `if` and
 /// `elif` branches.
 #[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(deserialize = "'de: 'a"))
+)]
 pub struct Branch<'a> {
     /// The condition of the branch.
     pub cond: Block<'a>,
@@ -92,7 +205,8 @@ pub struct Branch<'a> {
 }
 
 /// Represents the name of a variable or non-primitive function in the AST.
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Name<'a>(pub &'a str);
 
 impl<'a> Display for Name<'a> {
@@ -103,6 +217,7 @@ impl<'a> Display for Name<'a> {
 
 /// The different binary operators that Pijama's syntax supports.
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BinOp {
     /// Addition operator.
     Add,
@@ -114,6 +229,8 @@ pub enum BinOp {
     Div,
     /// Remainder/Modulo operator.
     Rem,
+    /// Exponentiation operator.
+    Pow,
     /// Logical And operator.
     And,
     /// Logical Or operator.
@@ -142,7 +259,9 @@ pub enum BinOp {
     Gte,
 }
 
-impl<'a> Display for BinOp {
+/// Binary operators are displayed using the same spelling used to write them in Pijama's source
+/// code.
+impl Display for BinOp {
     fn fmt(&self, f: &mut Formatter) -> Result {
         use BinOp::*;
         match self {
@@ -151,6 +270,7 @@ impl<'a> Display for BinOp {
             Mul => write!(f, "*"),
             Div => write!(f, "/"),
             Rem => write!(f, "%"),
+            Pow => write!(f, "**"),
             And => write!(f, "&&"),
             Or => write!(f, "||"),
             BitAnd => write!(f, "&"),
@@ -170,6 +290,7 @@ impl<'a> Display for BinOp {
 
 /// The unary operators that Pijama's syntax supports.
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UnOp {
     /// Arithmetic Negation operator.
     Neg,
@@ -177,7 +298,9 @@ pub enum UnOp {
     Not,
 }
 
-impl<'a> Display for UnOp {
+/// Unary operators are displayed using the same spelling used to write them in Pijama's source
+/// code.
+impl Display for UnOp {
     fn fmt(&self, f: &mut Formatter) -> Result {
         use UnOp::*;
         match self {
@@ -189,6 +312,7 @@ impl<'a> Display for UnOp {
 
 /// The literal values that Pijama's syntax supports.
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Literal {
     /// Boolean Literal.
     Bool(bool),
@@ -210,7 +334,8 @@ impl From<bool> for Literal {
     }
 }
 
-impl<'a> Display for Literal {
+/// Literals are displayed using the same syntax used to write them in Pijama's source code.
+impl Display for Literal {
     fn fmt(&self, f: &mut Formatter) -> Result {
         use Literal::*;
         match self {
@@ -223,17 +348,33 @@ impl<'a> Display for Literal {
 
 /// The primitives that Pijama's syntax supports.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Primitive {
     /// Built-in Print primitive.
     Print,
+    /// Built-in arithmetic negation primitive.
+    Neg,
+    /// Built-in debugging primitive that prints its argument and returns it.
+    Dbg,
+    /// Built-in logical negation primitive.
+    Not,
+    /// Built-in primitive that evaluates its argument and discards it.
+    Ignore,
+    /// Built-in primitive that reads an integer from the input.
+    ReadInt,
 }
 
-impl<'a> Display for Primitive {
+impl Display for Primitive {
     fn fmt(&self, f: &mut Formatter) -> Result {
         use Primitive::*;
 
         match self {
             Print => write!(f, "print"),
+            Neg => write!(f, "neg"),
+            Dbg => write!(f, "dbg"),
+            Not => write!(f, "not"),
+            Ignore => write!(f, "ignore"),
+            ReadInt => write!(f, "read_int"),
         }
     }
 }
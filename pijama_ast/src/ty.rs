@@ -8,6 +8,7 @@ use crate::location::Located;
 /// This type must only represent the kinds of types that Pijama's AST can represent. Other `Ty`
 /// types exist with different purposes.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Ty {
     /// The type of booleans.
     Bool,
@@ -26,6 +27,7 @@ pub enum Ty {
 /// This represents an annotation of an AST item with a type and is used to represent any type
 /// annotations written by the user.
 #[derive(Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TyAnnotation<I: Debug + Eq + PartialEq> {
     /// The annotated item.
     pub item: I,
@@ -1,7 +1,9 @@
 //! Trait to traverse the AST.
 use crate::{
     location::Located,
-    node::{BinOp, Block, Branch, Expression, Literal, Name, Node, Primitive, Statement, UnOp},
+    node::{
+        BinOp, Block, Branch, Expression, Literal, Name, Node, Param, Primitive, Statement, UnOp,
+    },
     ty::TyAnnotation,
 };
 
@@ -14,7 +16,7 @@ use crate::{
 /// There are two kinds of methods:
 /// - The `visit_<foo>` methods: where the code specific to your visiting resides.
 /// - The `super_<foo>` methods: that destructure each component and take care of the actual
-/// visiting.
+///   visiting.
 ///
 /// Most of the time, the `visit_<foo>` methods are the ones that should be implemented. It is
 /// important that the corresponding `super_<foo>` method is called at the end of the
@@ -36,15 +38,15 @@ pub trait NodeVisitor<'a> {
     /// Destructures a node to visit its children.
     fn super_node(&mut self, node: &Node<'a>) {
         match node {
-            Node::Stat(stat) => self.visit_statement(&stat),
-            Node::Expr(expr) => self.visit_expression(&expr),
+            Node::Stat(stat) => self.visit_statement(stat),
+            Node::Expr(expr) => self.visit_expression(expr),
         }
     }
     /// Destructures a statement to visit its children.
     fn super_statement(&mut self, stat: &Located<Statement<'a>>) {
         match &stat.content {
             Statement::Assign(annotation, expr) => self.visit_assign(annotation, expr),
-            Statement::FnDef(name, args, body) => self.visit_fn_def(name, args, body),
+            Statement::FnDef(name, args, body, _) => self.visit_fn_def(name, args, body),
         }
     }
     /// Destructures an expression to visit its children.
@@ -53,15 +55,17 @@ pub trait NodeVisitor<'a> {
             Expression::BinaryOp(op, expr1, expr2) => {
                 self.visit_binary_op(*op, expr1.as_ref(), expr2.as_ref())
             }
+            Expression::Chain(first, rest) => self.visit_chain(first.as_ref(), rest),
             Expression::UnaryOp(op, expr) => self.visit_unary_op(*op, expr.as_ref()),
             Expression::Cond(if_branch, branches, el_blk) => {
                 self.visit_cond(if_branch, branches, el_blk)
             }
             Expression::AnonFn(args, body) => self.visit_anon_fn(args, body),
-            Expression::Call(func, args) => self.visit_call(func.as_ref(), &args),
+            Expression::Call(func, args) => self.visit_call(func.as_ref(), args),
             Expression::Literal(literal) => self.visit_literal(literal),
             Expression::Name(name) => self.visit_name(name),
             Expression::PrimFn(primitive) => self.visit_prim_fn(*primitive),
+            Expression::Block(block) => self.visit_block(block),
         }
     }
     /// Destructures a binary operation to visit its children.
@@ -74,6 +78,17 @@ pub trait NodeVisitor<'a> {
         self.visit_expression(expr1);
         self.visit_expression(expr2);
     }
+    /// Destructures a chain of comparisons to visit its children.
+    fn super_chain(
+        &mut self,
+        first: &Located<Expression<'a>>,
+        rest: &[(BinOp, Located<Expression<'a>>)],
+    ) {
+        self.visit_expression(first);
+        for (_, expr) in rest {
+            self.visit_expression(expr);
+        }
+    }
     /// Destructures a unary operation to visit its children.
     fn super_unary_op(&mut self, _op: UnOp, expr: &Located<Expression<'a>>) {
         self.visit_expression(expr);
@@ -106,12 +121,15 @@ pub trait NodeVisitor<'a> {
     fn super_fn_def(
         &mut self,
         name: &Located<Name<'a>>,
-        args: &[TyAnnotation<Located<Name<'a>>>],
+        params: &[Param<'a>],
         body: &TyAnnotation<Block<'a>>,
     ) {
         self.visit_name(&name.content);
-        for ann in args {
-            self.visit_name(&ann.item.content);
+        for param in params {
+            self.visit_name(&param.ann.item.content);
+            if let Some(default) = &param.default {
+                self.visit_literal(&default.content);
+            }
         }
         self.visit_block(&body.item);
     }
@@ -164,6 +182,14 @@ pub trait NodeVisitor<'a> {
     ) {
         self.super_binary_op(op, expr1, expr2);
     }
+    /// Specifies how chains of comparisons should be visited.
+    fn visit_chain(
+        &mut self,
+        first: &Located<Expression<'a>>,
+        rest: &[(BinOp, Located<Expression<'a>>)],
+    ) {
+        self.super_chain(first, rest);
+    }
     /// Specifies how unary operations should be visited.
     fn visit_unary_op(&mut self, op: UnOp, expr: &Located<Expression<'a>>) {
         self.super_unary_op(op, expr);
@@ -188,10 +214,10 @@ pub trait NodeVisitor<'a> {
     fn visit_fn_def(
         &mut self,
         name: &Located<Name<'a>>,
-        args: &[TyAnnotation<Located<Name<'a>>>],
+        params: &[Param<'a>],
         body: &TyAnnotation<Block<'a>>,
     ) {
-        self.super_fn_def(name, args, body);
+        self.super_fn_def(name, params, body);
     }
     /// Specifies how anonymous functions should be visited.
     fn visit_anon_fn(
@@ -10,7 +10,7 @@ use pijama_tycheck::ty_check;
 
 use pijama_lir::Term;
 
-use pijama_machine::MachineBuilder;
+use pijama_machine::{arithmetic::WrappingArithmetic, MachineBuilder};
 
 use pijama_driver::LangResult;
 
@@ -18,7 +18,7 @@ fn compile(input: &str) -> LangResult<Term> {
     let ast = parse(input)?;
     let mir = MirTerm::from_ast(ast)?;
     ty_check(&mir)?;
-    Ok(Term::from_mir(mir))
+    Ok(Term::from_mir(mir)?)
 }
 
 fn arithmetic(c: &mut Criterion) {
@@ -28,6 +28,17 @@ fn arithmetic(c: &mut Criterion) {
     c.bench_function("arithmetic", |b| b.iter(|| machine.evaluate(term.clone())));
 }
 
+fn arithmetic_wrapping(c: &mut Criterion) {
+    let input = include_str!("arithmetic.pj");
+    let term = compile(input).unwrap();
+    let mut machine = MachineBuilder::default()
+        .with_arithmetic(WrappingArithmetic)
+        .build();
+    c.bench_function("arithmetic_wrapping", |b| {
+        b.iter(|| machine.evaluate(term.clone()))
+    });
+}
+
 fn logic(c: &mut Criterion) {
     let input = include_str!("logic.pj");
     let term = compile(input).unwrap();
@@ -137,6 +148,7 @@ fn adler32(c: &mut Criterion) {
 criterion_group!(
     benches,
     arithmetic,
+    arithmetic_wrapping,
     logic,
     factorial,
     factorial_tail,
@@ -0,0 +1,168 @@
+//! Resolution of `include "path"` directives.
+//!
+//! Included files are resolved relative to the file including them and loaded before it. A file
+//! that is included more than once is only loaded the first time.
+use thiserror::Error;
+
+use std::{
+    collections::HashSet,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+
+use pijama_ast::location::{Located, Location};
+
+/// The keyword used by include directives.
+const INCLUDE: &str = "include";
+
+#[derive(Error, Debug, Eq, PartialEq)]
+#[error("{kind}")]
+pub struct IncludeError {
+    loc: Location,
+    kind: IncludeErrorKind,
+}
+
+#[derive(Error, Debug, Eq, PartialEq)]
+pub enum IncludeErrorKind {
+    #[error("Could not read `{path}`: {msg}")]
+    Io { path: PathBuf, msg: String },
+    #[error("Circular include of `{0}`")]
+    Cycle(PathBuf),
+}
+
+impl IncludeError {
+    pub fn loc(&self) -> Location {
+        self.loc
+    }
+
+    pub fn kind(&self) -> &IncludeErrorKind {
+        &self.kind
+    }
+}
+
+/// A file loaded by `load`.
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    /// Path of the file. This is the path given to `load` for the root file and the path in the
+    /// directive joined to the directory of the including file for every other file.
+    pub path: PathBuf,
+    /// Source of the file with its include directives blanked out.
+    pub source: String,
+}
+
+/// Loads the file in `path` and all the files it includes.
+///
+/// The files are returned indexed by the file of their locations: the file in `path` has index
+/// `0` and the rest are numbered in the order they are found. The include directives are blanked
+/// out from the returned sources to keep the locations of the remaining code intact.
+///
+/// The indices of the files are also returned in the order they must be evaluated: every file
+/// comes after all the files it includes, so the file in `path` is always the last one.
+pub fn load(path: &Path) -> Result<(Vec<SourceFile>, Vec<usize>), IncludeError> {
+    let mut loader = Loader::default();
+    loader.load(path, Location::new(0, 0))?;
+    Ok((loader.files, loader.order))
+}
+
+#[derive(Default)]
+struct Loader {
+    /// Files that are currently being loaded. Finding one of these again means there is a cycle.
+    stack: Vec<PathBuf>,
+    /// Files that have been completely loaded.
+    loaded: HashSet<PathBuf>,
+    /// The loaded files, in the order they were found.
+    files: Vec<SourceFile>,
+    /// The indices of the loaded files, in evaluation order.
+    order: Vec<usize>,
+}
+
+impl Loader {
+    /// Loads a single file. `loc` is the location of the directive that included it.
+    fn load(&mut self, path: &Path, loc: Location) -> Result<(), IncludeError> {
+        let io_error = |err: std::io::Error| IncludeError {
+            loc,
+            kind: IncludeErrorKind::Io {
+                path: path.to_owned(),
+                msg: err.to_string(),
+            },
+        };
+
+        let canonical = path.canonicalize().map_err(io_error)?;
+
+        if self.stack.contains(&canonical) {
+            return Err(IncludeError {
+                loc,
+                kind: IncludeErrorKind::Cycle(path.to_owned()),
+            });
+        }
+
+        if self.loaded.contains(&canonical) {
+            return Ok(());
+        }
+
+        let mut source = read_to_string(path).map_err(io_error)?;
+        let file = self.files.len();
+        let includes = take_includes(&mut source, file);
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        self.files.push(SourceFile {
+            path: path.to_owned(),
+            source,
+        });
+
+        self.stack.push(canonical);
+        for include in includes {
+            self.load(&dir.join(include.content), include.loc)?;
+        }
+        let canonical = self.stack.pop().unwrap();
+
+        self.loaded.insert(canonical);
+        self.order.push(file);
+
+        Ok(())
+    }
+}
+
+/// Removes the include directives at the beginning of `source` and returns the included paths.
+///
+/// Only blank lines and comments are allowed before or between the directives. Each directive is
+/// replaced by whitespace so the locations of the rest of the source are preserved. The directives
+/// are located inside the file with index `file`.
+fn take_includes(source: &mut String, file: usize) -> Vec<Located<PathBuf>> {
+    let mut includes = Vec::new();
+    let mut start = 0;
+
+    for line in source.split('\n') {
+        let end = start + line.len();
+        let trimmed = line.trim();
+
+        if !trimmed.is_empty() && !trimmed.starts_with('#') {
+            match parse_directive(trimmed) {
+                Some(path) => {
+                    let loc = Location::new(start, end).in_file(file);
+                    includes.push(loc.with_content(path))
+                }
+                None => break,
+            }
+        }
+
+        start = end + 1;
+    }
+
+    for include in &includes {
+        let Location { start, end, .. } = include.loc;
+        source.replace_range(start..end, &" ".repeat(end - start));
+    }
+
+    includes
+}
+
+/// Parses a line of the form `include "path"`.
+fn parse_directive(line: &str) -> Option<PathBuf> {
+    let rest = line.strip_prefix(INCLUDE)?;
+    let path = rest.trim_start().strip_prefix('"')?.strip_suffix('"')?;
+    if rest.starts_with(char::is_whitespace) && !path.contains('"') {
+        Some(PathBuf::from(path))
+    } else {
+        None
+    }
+}
@@ -1,22 +1,89 @@
 use thiserror::Error;
 
-use std::io::Write;
+use std::{collections::HashMap, io::Write, path::Path};
 
-use pijama_parser::{parse, ParsingError};
+use pijama_ast::{
+    analysis::all_warnings,
+    location::{Located, Location},
+    node::{Block, Node},
+};
+
+use pijama_parser::{parse, parse_file, ParsingError};
 
 use pijama_mir::{LowerError, Term as MirTerm};
 
-use pijama_tycheck::{ty_check, TyError};
+use pijama_ty::Ty;
+
+use pijama_tycheck::{annotate as ty_annotate, ty_check_with_prints, TyError};
 
-use pijama_lir::Term as LirTerm;
+use pijama_lir::{ResolveError, Term as LirTerm};
 
 use pijama_machine::{
-    arithmetic::{Arithmetic, CheckedArithmetic, OverflowArithmetic},
-    Machine, MachineBuilder,
+    arithmetic::{Arithmetic, CheckedArithmetic, OverflowArithmetic, WrappingArithmetic},
+    env::Env,
+    hook::Hook,
+    EvalError, Machine, MachineBuilder, Strategy,
 };
 
+pub use include::{IncludeError, IncludeErrorKind, SourceFile};
+pub use pijama_ast::analysis::Warning;
+pub use pijama_tycheck::{AnnotatedKind, AnnotatedTerm};
+
+mod include;
+
 pub type LangResult<T> = Result<T, LangError>;
 
+/// Options controlling how a program is compiled and evaluated.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Config {
+    /// Report integer overflow during evaluation as a runtime error.
+    pub overflow_check: bool,
+    /// Let integer operations wrap around silently on overflow, without any checks. Ignored if
+    /// `overflow_check` is set.
+    pub wrapping: bool,
+    /// Reject programs whose type still has type variables after type-checking.
+    pub strict_types: bool,
+    /// Annotate every printed value with its type.
+    pub print_types: bool,
+    /// Inline calls to small non-recursive functions before evaluation.
+    pub inline: bool,
+    /// Replace conditionals with a literal condition by the branch they take before evaluation.
+    pub simplify: bool,
+    /// Evaluate repeated pure subexpressions only once.
+    pub cse: bool,
+    /// Only functions marked with `rec` can be recursive.
+    pub explicit_rec: bool,
+    /// Stop the evaluation if the program prints more than this number of bytes.
+    pub max_output_bytes: Option<usize>,
+    /// Evaluate the arguments of functions before calling them instead of doing it lazily.
+    pub eager: bool,
+    /// Stop the evaluation if it takes more than this number of steps.
+    pub max_steps: Option<usize>,
+}
+
+impl Config {
+    /// Returns the evaluation strategy selected by this configuration.
+    fn strategy(&self) -> Strategy {
+        if self.eager {
+            Strategy::Eager
+        } else {
+            Strategy::Lazy
+        }
+    }
+}
+
+/// The maximum size of a function to be inlined, as returned by `Term::size`.
+const INLINE_MAX_SIZE: usize = 32;
+
+/// The result of running a program successfully.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RunOutcome {
+    /// Everything printed by the program.
+    pub output: String,
+    /// The warnings found while compiling the program.
+    pub warnings: Vec<Warning>,
+}
+
 #[derive(Error, Debug, Eq, PartialEq)]
 pub enum LangError {
     #[error("{0}")]
@@ -25,30 +92,234 @@ pub enum LangError {
     Parse(#[from] ParsingError),
     #[error("{0}")]
     Lower(#[from] LowerError),
+    #[error("{0}")]
+    Resolve(#[from] ResolveError),
+    #[error("{0}")]
+    Include(#[from] IncludeError),
+    #[error("{0}")]
+    Runtime(#[from] EvalError),
 }
 
-pub fn run_with_machine<W: Write, A: Arithmetic>(
+impl LangError {
+    /// Returns the location of the error in the source code.
+    pub fn loc(&self) -> Location {
+        match self {
+            LangError::Ty(error) => error.loc(),
+            LangError::Parse(error) => error.loc(),
+            LangError::Lower(error) => error.loc(),
+            LangError::Resolve(error) => error.loc(),
+            LangError::Include(error) => error.loc(),
+            LangError::Runtime(error) => error.loc(),
+        }
+    }
+}
+
+/// Checks that `input` parses, lowers and type-checks without evaluating it.
+///
+/// Returns the first error found by any of these phases. The options of `config` that only
+/// affect how the program is optimized or run are ignored.
+pub fn check(input: &str, config: Config) -> LangResult<()> {
+    let ast = parse(input)?;
+    let mir = lower(ast, config)?;
+    type_check(&mir, config)?;
+    Ok(())
+}
+
+/// Type-checks `input` and returns its MIR with every subterm annotated with its type.
+pub fn annotate(input: &str) -> LangResult<AnnotatedTerm<'_>> {
+    let ast = parse(input)?;
+    let mir = MirTerm::from_ast(ast)?;
+    Ok(ty_annotate(&mir)?)
+}
+
+/// Parses `input` and returns the warnings found by the analyses done over it.
+pub fn warnings(input: &str) -> LangResult<Vec<Warning>> {
+    let ast = parse(input)?;
+    Ok(all_warnings(&ast))
+}
+
+pub fn run_with_machine<W: Write, A: Arithmetic, H: Hook>(
     input: &str,
-    mut machine: Machine<W, A>,
+    machine: Machine<W, A, H>,
+    config: Config,
 ) -> LangResult<()> {
     let ast = parse(input)?;
-    let mir = MirTerm::from_ast(ast)?;
-    let _ty = ty_check(&mir)?;
-    let lir = LirTerm::from_mir(mir);
-    let _res = machine.evaluate(lir);
+    run_ast(ast, machine, config)?;
     Ok(())
 }
 
-pub fn run(input: &str, overflow_check: bool) -> LangResult<()> {
-    if overflow_check {
-        let machine = MachineBuilder::default()
-            .with_arithmetic(CheckedArithmetic)
-            .build();
-        run_with_machine(input, machine)
+/// Runs the file in `path` and returns everything it printed.
+///
+/// The file can include other files using `include "path"` directives at its beginning. These
+/// paths are resolved relative to the including file and the bindings of the included files are
+/// in scope for the rest of the program.
+///
+/// The locations of the returned errors are inside the file with the same index in the files
+/// returned by `load_files`.
+pub fn run_file(path: &Path, config: Config) -> LangResult<String> {
+    let (files, order) = include::load(path)?;
+    let mut blocks = order
+        .into_iter()
+        .map(|file| parse_file(&files[file].source, file))
+        .collect::<Result<Vec<_>, _>>()?;
+    // The last block is the one from `path` itself.
+    let mut ast = blocks.pop().unwrap();
+
+    for block in blocks.into_iter().rev() {
+        ast.nodes.push_front(Node::Expr(*block.expr));
+        for node in block.nodes.into_iter().rev() {
+            ast.nodes.push_front(node);
+        }
+    }
+
+    let mut output = Vec::default();
+    let builder = MachineBuilder::default().with_env(Env::new(&mut output));
+    run_ast_with_config(ast, builder, config)?;
+    Ok(String::from_utf8(output).expect("Output is not valid UTF-8"))
+}
+
+/// Loads the file in `path` and all the files it includes.
+///
+/// The files are indexed by the file of their locations. The file in `path` has index `0` and the
+/// rest are numbered in the order they are found.
+pub fn load_files(path: &Path) -> LangResult<Vec<SourceFile>> {
+    let (files, _) = include::load(path)?;
+    Ok(files)
+}
+
+/// A program that has been compiled and is ready to be run.
+///
+/// Compiling a program once and running it several times avoids parsing, lowering and
+/// type-checking it again for each run.
+#[derive(Debug, Clone)]
+pub struct Program {
+    term: LirTerm,
+    warnings: Vec<Warning>,
+}
+
+impl Program {
+    /// Returns the warnings found while compiling the program.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Runs the program using `machine`.
+    ///
+    /// The machine can be inspected afterwards, for example to get the statistics of the run.
+    pub fn run<W: Write, A: Arithmetic, H: Hook>(
+        &self,
+        machine: &mut Machine<W, A, H>,
+    ) -> LangResult<()> {
+        machine.evaluate(self.term.clone())?;
+        Ok(())
+    }
+}
+
+/// Compiles `input` into a program that can be run several times.
+///
+/// The `overflow_check`, `wrapping`, `max_output_bytes`, `eager` and `max_steps` options are
+/// ignored because they only affect the machine running the program.
+pub fn compile(input: &str, config: Config) -> LangResult<Program> {
+    let ast = parse(input)?;
+    compile_ast(ast, config)
+}
+
+/// Compiles and evaluates an AST, returning the warnings found while compiling it.
+fn run_ast<W: Write, A: Arithmetic, H: Hook>(
+    ast: Block<'_>,
+    mut machine: Machine<W, A, H>,
+    config: Config,
+) -> LangResult<Vec<Warning>> {
+    let program = compile_ast(ast, config)?;
+    program.run(&mut machine)?;
+    Ok(program.warnings)
+}
+
+/// Lowers an AST into MIR, detecting recursive functions unless `config.explicit_rec` is set.
+fn lower(ast: Block<'_>, config: Config) -> LangResult<Located<MirTerm<'_>>> {
+    let mir = if config.explicit_rec {
+        MirTerm::from_ast_with_explicit_rec(ast)?
+    } else {
+        MirTerm::from_ast(ast)?
+    };
+    Ok(mir)
+}
+
+/// Type-checks a MIR term, rejecting ambiguous types if `config.strict_types` is set.
+///
+/// Returns the types of the values printed by the term, indexed by the location of each `print`.
+fn type_check(mir: &Located<MirTerm<'_>>, config: Config) -> LangResult<HashMap<Location, Ty>> {
+    let (ty, print_tys) = ty_check_with_prints(mir)?;
+    if config.strict_types && !ty.content.is_concrete() {
+        return Err(TyError::AmbiguousType(ty).into());
+    }
+    Ok(print_tys)
+}
+
+fn compile_ast(ast: Block<'_>, config: Config) -> LangResult<Program> {
+    let warnings = all_warnings(&ast);
+    let mut mir = lower(ast, config)?;
+    let print_tys = type_check(&mir, config)?;
+    if config.inline {
+        mir.content.inline(INLINE_MAX_SIZE);
+    }
+    if config.simplify {
+        mir.content.simplify();
+    }
+    if config.cse {
+        mir.content.eliminate_common_subterms();
+    }
+    let term = if config.print_types {
+        LirTerm::from_mir_with_print_tys(mir, print_tys)?
     } else {
-        let machine = MachineBuilder::default()
-            .with_arithmetic(OverflowArithmetic)
-            .build();
-        run_with_machine(input, machine)
+        LirTerm::from_mir(mir)?
+    };
+    Ok(Program { term, warnings })
+}
+
+/// Compiles and evaluates an AST using a machine built from `builder` and configured by `config`,
+/// returning the warnings found while compiling it.
+fn run_ast_with_config<W: Write>(
+    ast: Block<'_>,
+    builder: MachineBuilder<W, OverflowArithmetic>,
+    config: Config,
+) -> LangResult<Vec<Warning>> {
+    let builder = builder
+        .with_max_output_bytes(config.max_output_bytes)
+        .with_strategy(config.strategy())
+        .with_max_steps(config.max_steps);
+    if config.overflow_check {
+        run_ast(
+            ast,
+            builder.with_arithmetic(CheckedArithmetic).build(),
+            config,
+        )
+    } else if config.wrapping {
+        run_ast(
+            ast,
+            builder.with_arithmetic(WrappingArithmetic).build(),
+            config,
+        )
+    } else {
+        run_ast(ast, builder.build(), config)
     }
 }
+
+pub fn run(input: &str, config: Config) -> LangResult<()> {
+    let ast = parse(input)?;
+    run_ast_with_config(ast, MachineBuilder::default(), config)?;
+    Ok(())
+}
+
+/// Runs `input` and returns everything it printed together with the warnings found while
+/// compiling it.
+pub fn run_with_warnings(input: &str, config: Config) -> LangResult<RunOutcome> {
+    let ast = parse(input)?;
+    let mut output = Vec::default();
+    let builder = MachineBuilder::default().with_env(Env::new(&mut output));
+    let warnings = run_ast_with_config(ast, builder, config)?;
+    Ok(RunOutcome {
+        output: String::from_utf8(output).expect("Output is not valid UTF-8"),
+        warnings,
+    })
+}
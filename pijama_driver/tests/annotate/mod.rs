@@ -0,0 +1,69 @@
+use pijama_ast::node::{BinOp, Literal};
+use pijama_driver::{annotate, AnnotatedKind, LangResult};
+use pijama_ty::Ty;
+
+#[test]
+fn sum() -> LangResult<()> {
+    let term = annotate("1 + 2")?;
+    assert_eq!(Ty::Int, term.ty);
+    match term.kind {
+        AnnotatedKind::BinaryOp(BinOp::Add, t1, t2) => {
+            assert!(matches!(t1.kind, AnnotatedKind::Lit(Literal::Number(1))));
+            assert_eq!(Ty::Int, t1.ty);
+            assert!(matches!(t2.kind, AnnotatedKind::Lit(Literal::Number(2))));
+            assert_eq!(Ty::Int, t2.ty);
+        }
+        kind => panic!("Unexpected term {:?}", kind),
+    }
+    Ok(())
+}
+
+#[test]
+fn nested_application() -> LangResult<()> {
+    let input = include_str!("nested_application.pj");
+    let term = annotate(input)?;
+    assert_eq!(Ty::Bool, term.ty);
+    // Skip the binding of `f` to reach the call `f(1, 2)`.
+    let call = match term.kind {
+        AnnotatedKind::Let(_, _, _, body) => body,
+        kind => panic!("Unexpected term {:?}", kind),
+    };
+    match call.kind {
+        AnnotatedKind::App(partial, _) => {
+            assert_eq!(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Bool)), partial.ty)
+        }
+        kind => panic!("Unexpected term {:?}", kind),
+    }
+    Ok(())
+}
+
+#[test]
+fn cond_binding() -> LangResult<()> {
+    let input = include_str!("cond_binding.pj");
+    let term = annotate(input)?;
+    assert_eq!(Ty::Int, term.ty);
+    // Skip the bindings of the `else` block and of `r` to reach the conditional.
+    let cond = match term.kind {
+        AnnotatedKind::Let(_, _, _, body) => match body.kind {
+            AnnotatedKind::Let(_, _, _, cond) => cond,
+            kind => panic!("Unexpected term {:?}", kind),
+        },
+        kind => panic!("Unexpected term {:?}", kind),
+    };
+    let call = match cond.kind {
+        AnnotatedKind::Cond(_, _, call) => call,
+        kind => panic!("Unexpected term {:?}", kind),
+    };
+    // The call to the `else` block and its subterms share the same location.
+    assert_eq!(Ty::Int, call.ty);
+    match call.kind {
+        AnnotatedKind::App(func, arg) => {
+            assert_eq!(call.loc, func.loc);
+            assert_eq!(call.loc, arg.loc);
+            assert_eq!(Ty::Arrow(Box::new(Ty::Unit), Box::new(Ty::Int)), func.ty);
+            assert_eq!(Ty::Unit, arg.ty);
+        }
+        kind => panic!("Unexpected term {:?}", kind),
+    }
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use pijama_ast::node::{Expression, Literal};
+
+use pijama_parser::parse;
+
+use pijama_driver::LangResult;
+
+#[test]
+fn literal_number() {
+    assert_eq!("0", Literal::Number(0).to_string());
+    assert_eq!("42", Literal::Number(42).to_string());
+    assert_eq!("-42", Literal::Number(-42).to_string());
+}
+
+#[test]
+fn literal_bool() {
+    assert_eq!("true", Literal::Bool(true).to_string());
+    assert_eq!("false", Literal::Bool(false).to_string());
+}
+
+#[test]
+fn literal_unit() {
+    assert_eq!("unit", Literal::Unit.to_string());
+}
+
+#[test]
+fn bin_op() -> LangResult<()> {
+    let spellings = [
+        "+", "-", "*", "/", "%", "**", "&&", "||", "&", "|", "^", ">>", "<<", "==", "!=", "<", ">",
+        "<=", ">=",
+    ];
+    for spelling in spellings.iter() {
+        let input = format!("x {} y", spelling);
+        match parse(&input)?.expr.content {
+            Expression::BinaryOp(op, _, _) => assert_eq!(*spelling, op.to_string()),
+            expr => panic!("expected a binary operation, found {:?}", expr),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn un_op() -> LangResult<()> {
+    for spelling in ["-", "!"].iter() {
+        let input = format!("{}x", spelling);
+        match parse(&input)?.expr.content {
+            Expression::UnaryOp(op, _) => assert_eq!(*spelling, op.to_string()),
+            expr => panic!("expected a unary operation, found {:?}", expr),
+        }
+    }
+    Ok(())
+}
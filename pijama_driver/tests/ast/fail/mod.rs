@@ -1,24 +1,31 @@
-use crate::{test_type, util::dummy_loc};
+use crate::{
+    test_type,
+    util::{dummy_loc, DummyLoc},
+};
 
 use pijama_driver::LangError;
 use pijama_mir::LowerError;
-
-test_type!(
-    detect_indirect_recursion,
-    Err(LangError::Lower(LowerError::RequiredTy(dummy_loc())))
-);
+use pijama_ty::Ty;
+use pijama_tycheck::TyError;
 
 test_type!(
     detect_recursion_after_shadowing,
-    Err(LangError::Lower(LowerError::RequiredTy(dummy_loc())))
+    Err(LangError::Ty(TyError::AmbiguousType(
+        Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Var(3))).loc()
+    ))),
+    "foo"
 );
 
 test_type!(
-    detect_recursion_after_shadowing_2,
-    Err(LangError::Lower(LowerError::RequiredTy(dummy_loc())))
+    anon_fn_with_ret_ty,
+    Err(LangError::Lower(LowerError::AnonWithTy(dummy_loc()))),
+    "Int"
 );
 
 test_type!(
-    detect_recursion_inside_functions,
-    Err(LangError::Lower(LowerError::RequiredTy(dummy_loc())))
+    rec_without_ret_ty,
+    Err(LangError::Ty(TyError::AmbiguousType(
+        Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Var(2))).loc()
+    ))),
+    "count"
 );
@@ -0,0 +1,22 @@
+use std::include_str;
+
+use pijama_ast::{analysis::recursive_groups, node::Name};
+
+use pijama_parser::parse;
+
+use pijama_driver::LangResult;
+
+#[test]
+fn mutual_recursion() -> LangResult<()> {
+    let input = include_str!("mutual_recursion.pj");
+    let groups = recursive_groups(&parse(input)?);
+    assert_eq!(
+        vec![
+            vec![Name("is_even"), Name("is_odd")],
+            vec![Name("main")],
+            vec![Name("is_zero")],
+        ],
+        groups
+    );
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use pijama_ast::{
+    location::{Located, Location},
+    node::{BinOp, Expression, Literal, Name},
+};
+
+use pijama_driver::LangResult;
+use pijama_parser::parse;
+
+#[test]
+fn empty_span() {
+    let located = Located::new((), Location::new(3, 3));
+    assert_eq!(Location::new(3, 3), located.loc);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "Location 3..2 ends before it starts")]
+fn inverted_span() {
+    Located::new((), Location::new(3, 2));
+}
+
+#[test]
+fn contains() {
+    let loc = Location::new(3, 5);
+    assert!(!loc.contains(2), "before start");
+    assert!(loc.contains(3), "start");
+    assert!(loc.contains(4), "inside");
+    assert!(!loc.contains(5), "end");
+    assert!(!Location::new(3, 3).contains(3), "empty");
+}
+
+#[test]
+fn add() {
+    assert_eq!(
+        Location::new(3, 9),
+        Location::new(3, 5) + Location::new(7, 9)
+    );
+    // Locations in different files are not joined.
+    let other = Location::new(0, 1).in_file(1);
+    assert_eq!(Location::new(3, 5), Location::new(3, 5) + other);
+    assert_eq!(other, other + Location::new(3, 5));
+}
+
+#[test]
+fn node_at() -> LangResult<()> {
+    let input = "x = 1 + foo(2)\nx";
+    let block = parse(input)?;
+    let expr_at = |offset| block.node_at(offset).map(|expr| &expr.content);
+
+    assert_eq!(None, expr_at(0), "bound name");
+    assert_eq!(Some(&Expression::Literal(Literal::Number(1))), expr_at(4));
+    assert!(
+        matches!(expr_at(6), Some(Expression::BinaryOp(BinOp::Add, _, _))),
+        "operator"
+    );
+    assert_eq!(Some(&Expression::Name(Name("foo"))), expr_at(9));
+    assert!(
+        matches!(expr_at(11), Some(Expression::Call(_, _))),
+        "parenthesis"
+    );
+    assert_eq!(Some(&Expression::Literal(Literal::Number(2))), expr_at(12));
+    assert_eq!(Some(&Expression::Name(Name("x"))), expr_at(15));
+    assert_eq!(None, expr_at(16), "end of input");
+    Ok(())
+}
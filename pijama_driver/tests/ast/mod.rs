@@ -1,2 +1,6 @@
+mod display;
 mod fail;
+mod groups;
+mod location;
 mod pass;
+mod warnings;
@@ -0,0 +1,93 @@
+use std::include_str;
+
+use pijama_ast::{
+    analysis::{duplicate_bindings, Warning},
+    location::Location,
+};
+
+use pijama_parser::parse;
+
+use pijama_driver::{warnings, LangResult};
+
+#[test]
+fn shadowed() -> LangResult<()> {
+    let input = include_str!("shadowed.pj");
+    assert_eq!(
+        vec![
+            // `x = n + 1` shadows `x = 1`
+            Warning::Shadowed {
+                name: "x".to_owned(),
+                inner_loc: Location::new(32, 33),
+                outer_loc: Location::new(0, 1),
+            },
+            // `n = x * 2` shadows the parameter `n`
+            Warning::Shadowed {
+                name: "n".to_owned(),
+                inner_loc: Location::new(46, 47),
+                outer_loc: Location::new(12, 13),
+            },
+        ],
+        warnings(input)?
+    );
+    Ok(())
+}
+
+#[test]
+fn not_shadowed() -> LangResult<()> {
+    let input = include_str!("not_shadowed.pj");
+    assert_eq!(Vec::<Warning>::new(), warnings(input)?);
+    Ok(())
+}
+
+#[test]
+fn duplicate() -> LangResult<()> {
+    let input = include_str!("duplicate.pj");
+    // `x = y * 2` rebinds `x = 1` in the same scope
+    let duplicate = Warning::DuplicateBinding {
+        name: "x".to_owned(),
+        first_loc: Location::new(0, 1),
+        second_loc: Location::new(16, 17),
+    };
+    assert_eq!(vec![duplicate.clone()], duplicate_bindings(&parse(input)?));
+    // `x = n` is in a different scope
+    let shadowed = Warning::Shadowed {
+        name: "x".to_owned(),
+        inner_loc: Location::new(51, 52),
+        outer_loc: Location::new(16, 17),
+    };
+    assert_eq!(vec![duplicate, shadowed], warnings(input)?);
+    Ok(())
+}
+
+#[test]
+fn unreachable() -> LangResult<()> {
+    let input = include_str!("unreachable.pj");
+    // Location of the first `text` inside the first occurrence of `context`.
+    let loc = |text: &str, context: &str| {
+        let start = input.find(context).unwrap() + context.find(text).unwrap();
+        Location::new(start, start + text.len())
+    };
+    assert_eq!(
+        vec![
+            // `!b` negates `b`, so the `else` is unreachable
+            Warning::UnreachableBranch {
+                loc: loc("3", "else\n        3"),
+                cond_loc: loc("!b", "elif !b"),
+            },
+            // `b` is negated by `not(b)`, so `elif b == b` is unreachable
+            Warning::UnreachableBranch {
+                loc: loc("b == b", "elif b == b"),
+                cond_loc: loc("b", "elif b do\n        2\n    elif b == b"),
+            },
+        ],
+        warnings(input)?
+    );
+    Ok(())
+}
+
+#[test]
+fn reachable() -> LangResult<()> {
+    let input = include_str!("reachable.pj");
+    assert_eq!(Vec::<Warning>::new(), warnings(input)?);
+    Ok(())
+}
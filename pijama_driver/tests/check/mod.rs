@@ -0,0 +1,53 @@
+use std::{include_str, time::Duration};
+
+use pijama_driver::{check, Config, LangError, LangResult};
+use pijama_tycheck::TyError;
+
+use crate::panic_after;
+
+#[test]
+fn infinite_loop() -> LangResult<()> {
+    panic_after(Duration::from_secs(1), || {
+        let input = include_str!("infinite_loop.pj");
+        check(input, Config::default())
+    })
+}
+
+#[test]
+fn wrong_type() {
+    let input = include_str!("wrong_type.pj");
+    let result = check(input, Config::default());
+    assert!(matches!(result, Err(LangError::Ty(_))), "{:?}", result);
+}
+
+#[test]
+fn explicit_rec() {
+    let input = include_str!("../eval/explicit_rec_missing.pj");
+    assert_eq!(Ok(()), check(input, Config::default()));
+    let config = Config {
+        explicit_rec: true,
+        ..Config::default()
+    };
+    let result = check(input, config);
+    assert!(
+        matches!(&result, Err(LangError::Ty(TyError::Unbounded(_)))),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn strict_types() {
+    let input = include_str!("../type_check/strict/ambiguous_print.pj");
+    assert_eq!(Ok(()), check(input, Config::default()));
+    let config = Config {
+        strict_types: true,
+        ..Config::default()
+    };
+    let result = check(input, config);
+    assert!(
+        matches!(&result, Err(LangError::Ty(TyError::AmbiguousType(_)))),
+        "{:?}",
+        result
+    );
+}
@@ -1,8 +1,14 @@
-use std::{include_str, time::Duration};
+use std::{include_str, io::Cursor, time::Duration};
 
-use pijama_driver::{LangError, LangResult};
+use pijama_ast::location::Location;
+use pijama_driver::{
+    compile, run_with_machine, run_with_warnings, Config, LangError, LangResult, RunOutcome,
+    Warning,
+};
+use pijama_machine::{hook::Event, EvalError, PrintConfig};
+use pijama_tycheck::TyError;
 
-use crate::{panic_after, run};
+use crate::{machine_builder, panic_after, run, run_with_config};
 
 #[test]
 fn arithmetic() -> LangResult<()> {
@@ -12,6 +18,18 @@ fn arithmetic() -> LangResult<()> {
     Ok(())
 }
 
+#[test]
+fn arithmetic_with_types() -> LangResult<()> {
+    let input = include_str!("arithmetic.pj");
+    let config = Config {
+        print_types: true,
+        ..Config::default()
+    };
+    let output = run_with_config(input, config)?;
+    assert_eq!("121 : Int\n", output);
+    Ok(())
+}
+
 #[test]
 fn logic() -> LangResult<()> {
     let input = include_str!("logic.pj");
@@ -28,6 +46,65 @@ fn factorial() -> LangResult<()> {
     Ok(())
 }
 
+#[test]
+fn factorial_hook() -> LangResult<()> {
+    let input = include_str!("factorial.pj");
+    let mut output = Vec::default();
+    let mut events = Vec::new();
+    let machine = machine_builder(&mut output)
+        .with_hook(|event: Event<'_>| match event {
+            Event::Enter(name) => events.push(format!("enter {}", name)),
+            Event::Exit(name) => events.push(format!("exit {}", name)),
+            _ => (),
+        })
+        .build();
+    run_with_machine(input, machine, Config::default())?;
+
+    let mut expected = vec!["enter fact".to_owned(); 11];
+    expected.extend(vec!["exit fact".to_owned(); 11]);
+    assert_eq!(expected, events);
+    assert_eq!("3628800\n", String::from_utf8(output).unwrap());
+    Ok(())
+}
+
+#[test]
+fn cse() -> LangResult<()> {
+    let input = include_str!("cse.pj");
+    for (cse, calls) in [(false, 2), (true, 1)].iter() {
+        let mut output = Vec::default();
+        let mut entered = 0;
+        let machine = machine_builder(&mut output)
+            .with_hook(|event: Event<'_>| {
+                if let Event::Enter("square") = event {
+                    entered += 1;
+                }
+            })
+            .build();
+        let config = Config {
+            cse: *cse,
+            ..Config::default()
+        };
+        run_with_machine(input, machine, config)?;
+        assert_eq!(*calls, entered, "wrong number of calls with cse = {}", cse);
+        assert_eq!("18\n2\n2\n4\n", String::from_utf8(output).unwrap());
+    }
+    Ok(())
+}
+
+#[test]
+fn cse_keeps_order() {
+    let input = include_str!("cse_keeps_order.pj");
+    let config = Config {
+        cse: true,
+        ..Config::default()
+    };
+    let mut output = Vec::default();
+    let result = run_with_machine(input, machine_builder(&mut output).build(), config);
+    let loc = Location::new(33, 40);
+    assert_eq!(Err(LangError::Runtime(EvalError::Overflow { loc })), result);
+    assert_eq!("1\n", String::from_utf8(output).unwrap());
+}
+
 #[test]
 fn factorial_tail() -> LangResult<()> {
     let input = include_str!("factorial_tail.pj");
@@ -36,6 +113,30 @@ fn factorial_tail() -> LangResult<()> {
     Ok(())
 }
 
+#[test]
+fn factorial_tail_depth() -> LangResult<()> {
+    let max_depth = |input| -> LangResult<usize> {
+        let program = compile(input, Config::default())?;
+        let mut output = Vec::default();
+        let mut machine = machine_builder(&mut output).build();
+        program.run(&mut machine)?;
+        Ok(machine.stats().max_depth)
+    };
+    let depth = max_depth(include_str!("factorial.pj"))?;
+    let tail_depth = max_depth(include_str!("factorial_tail.pj"))?;
+    // Each one of the 10 pending multiplications of `fact(10)` needs its own frame. The tail
+    // version still needs some frames for the multiplications because the accumulator is only
+    // evaluated at the end, but none for the recursive calls.
+    assert!(depth >= 10, "depth is {}", depth);
+    assert!(
+        tail_depth < depth,
+        "{} is not less than {}",
+        tail_depth,
+        depth
+    );
+    Ok(())
+}
+
 #[test]
 fn fancy_max() -> LangResult<()> {
     let input = include_str!("fancy_max.pj");
@@ -160,6 +261,15 @@ fn and_short_circuit() -> LangResult<()> {
     })
 }
 
+#[test]
+fn short_circuit_effects() -> LangResult<()> {
+    let input = include_str!("short_circuit_effects.pj");
+    let output = run(input)?;
+    // `loud` only prints its argument when the right operand is evaluated.
+    assert_eq!("0\n1\n3\n1\n4\n1\n", output);
+    Ok(())
+}
+
 #[test]
 fn print_simple() -> LangResult<()> {
     let input = include_str!("print_simple.pj");
@@ -172,7 +282,24 @@ fn print_simple() -> LangResult<()> {
 fn print_simple_fn() -> LangResult<()> {
     let input = include_str!("print_simple_fn.pj");
     let output = run(input)?;
-    assert_eq!("(λ. _0)\n", output);
+    assert_eq!("foo\n", output);
+    Ok(())
+}
+
+#[test]
+fn print_named_fn() -> LangResult<()> {
+    let input = include_str!("print_named_fn.pj");
+    let output = run(input)?;
+    assert_eq!("f\n(λ. _0)\nadd\n", output);
+    Ok(())
+}
+
+#[test]
+fn print_partial_fn() -> LangResult<()> {
+    let input = include_str!("print_partial_fn.pj");
+    let output = run(input)?;
+    // A partial application is not the function bound to the name anymore.
+    assert_eq!("(λ. (1 + _0))\nadd\n", output);
     Ok(())
 }
 
@@ -196,7 +323,10 @@ fn print_print() -> LangResult<()> {
 fn print_redefine() {
     let input = include_str!("print_redefine.pj");
     let err = run(input).unwrap_err();
-    assert!(matches!(err, LangError::Parse(_)))
+    assert!(matches!(err, LangError::Parse(_)));
+    // The error points to the `=` after `print`.
+    let loc = err.loc();
+    assert_eq!("=", &input[loc.start..loc.end]);
 }
 
 #[test]
@@ -216,17 +346,49 @@ fn number_bases_arithmetic() -> LangResult<()> {
 }
 
 #[test]
-#[should_panic]
-fn add_overflow_panics() {
-    let input = include_str!("add_overflow_panics.pj");
-    run(input).ok();
+fn add_overflow() {
+    let input = include_str!("add_overflow.pj");
+    let loc = Location::new(0, 23);
+    assert_eq!(
+        Err(LangError::Runtime(EvalError::Overflow { loc })),
+        run(input)
+    );
+}
+
+#[test]
+fn neg_overflow() {
+    let input = include_str!("neg_overflow.pj");
+    let loc = Location::new(0, 23);
+    assert_eq!(
+        Err(LangError::Runtime(EvalError::Overflow { loc })),
+        run(input)
+    );
+}
+
+#[test]
+fn pow_overflow() {
+    let input = include_str!("pow_overflow.pj");
+    let loc = Location::new(0, 7);
+    assert_eq!(
+        Err(LangError::Runtime(EvalError::Overflow { loc })),
+        run(input)
+    );
+}
+
+#[test]
+fn negative_pow() {
+    let input = include_str!("negative_pow.pj");
+    let loc = Location::new(6, 18);
+    let error = EvalError::NegativeOperand { value: -1, loc };
+    assert_eq!(Err(LangError::Runtime(error)), run(input));
 }
 
 #[test]
-#[should_panic]
-fn neg_overflow_panics() {
-    let input = include_str!("neg_overflow_panics.pj");
-    run(input).ok();
+fn negative_shift() {
+    let input = include_str!("negative_shift.pj");
+    let loc = Location::new(6, 18);
+    let error = EvalError::NegativeOperand { value: -3, loc };
+    assert_eq!(Err(LangError::Runtime(error)), run(input));
 }
 
 #[test]
@@ -252,3 +414,483 @@ fn adler32() -> LangResult<()> {
     assert_eq!("300286872\n", output);
     Ok(())
 }
+
+#[test]
+fn seq_keeps_bindings() -> LangResult<()> {
+    let input = include_str!("seq_keeps_bindings.pj");
+    let output = run(input)?;
+    assert_eq!("1\n", output);
+    Ok(())
+}
+
+#[test]
+fn neg_as_value() -> LangResult<()> {
+    let input = include_str!("neg_as_value.pj");
+    let output = run(input)?;
+    assert_eq!("1\n", output);
+    Ok(())
+}
+
+#[test]
+fn not_as_value() -> LangResult<()> {
+    let input = include_str!("not_as_value.pj");
+    let output = run(input)?;
+    assert_eq!("1\n1\n", output);
+    Ok(())
+}
+
+#[test]
+fn bin_op_fn() -> LangResult<()> {
+    let input = include_str!("bin_op_fn.pj");
+    let output = run(input)?;
+    assert_eq!("3\n12\n1\n0\n0\n1\n2\n5\n", output);
+    Ok(())
+}
+
+#[test]
+fn interleaved() -> LangResult<()> {
+    let input = include_str!("interleaved.pj");
+    let output = run(input)?;
+    assert_eq!("5\n6\n", output);
+    Ok(())
+}
+
+#[test]
+fn chained_cmp() -> LangResult<()> {
+    let input = include_str!("chained_cmp.pj");
+    let output = run(input)?;
+    assert_eq!("1\n0\n2\n1\n0\n1\n", output);
+    Ok(())
+}
+
+#[test]
+fn section() -> LangResult<()> {
+    let input = include_str!("section.pj");
+    let output = run(input)?;
+    assert_eq!("42\n7\n1\n1024\n", output);
+    Ok(())
+}
+
+#[test]
+fn custom_op() -> LangResult<()> {
+    let input = include_str!("custom_op.pj");
+    let output = run(input)?;
+    assert_eq!("12\n42\n1\n12\n42\n", output);
+    Ok(())
+}
+
+#[test]
+fn adjacent_ops() -> LangResult<()> {
+    let input = include_str!("adjacent_ops.pj");
+    let output = run(input)?;
+    assert_eq!("-1\n-6\n0\n", output);
+    Ok(())
+}
+
+#[test]
+fn closure_scoping() -> LangResult<()> {
+    let input = include_str!("closure_scoping.pj");
+    let output = run(input)?;
+    assert_eq!("1\n11\n10\n", output);
+    Ok(())
+}
+
+#[test]
+fn max_output_bytes() {
+    let result = panic_after(Duration::from_secs(1), || {
+        let input = include_str!("print_forever.pj");
+        let mut output = Vec::default();
+        let machine = machine_builder(&mut output)
+            .with_max_output_bytes(Some(20))
+            .build();
+        let result = run_with_machine(input, machine, Config::default());
+        // Only whole lines are printed: "0\n" to "9\n" take 20 bytes.
+        assert_eq!(
+            "0\n1\n2\n3\n4\n5\n6\n7\n8\n9\n",
+            String::from_utf8(output).unwrap()
+        );
+        result
+    });
+    assert_eq!(
+        Err(LangError::Runtime(EvalError::OutputLimitExceeded(20))),
+        result
+    );
+}
+
+#[test]
+fn max_output_bytes_config() {
+    let result = panic_after(Duration::from_secs(1), || {
+        let input = include_str!("print_forever.pj");
+        let config = Config {
+            max_output_bytes: Some(20),
+            ..Config::default()
+        };
+        run_with_warnings(input, config)
+    });
+    assert_eq!(
+        Err(LangError::Runtime(EvalError::OutputLimitExceeded(20))),
+        result
+    );
+}
+
+#[test]
+fn max_output_bytes_per_evaluation() -> LangResult<()> {
+    let program = compile("print(12345)", Config::default())?;
+    let mut output = Vec::default();
+    let mut machine = machine_builder(&mut output)
+        .with_max_output_bytes(Some(10))
+        .build();
+    // The limit applies to each evaluation on its own.
+    program.run(&mut machine)?;
+    program.run(&mut machine)?;
+    assert_eq!("12345\n12345\n", String::from_utf8(output).unwrap());
+    Ok(())
+}
+
+#[test]
+fn lazy_unused_loop() -> LangResult<()> {
+    let input = include_str!("unused_loop.pj");
+    let output = run(input)?;
+    assert_eq!("1\n", output);
+    Ok(())
+}
+
+#[test]
+fn eager_unused_loop() {
+    let result = panic_after(Duration::from_secs(1), || {
+        let input = include_str!("unused_loop.pj");
+        let config = Config {
+            eager: true,
+            max_steps: Some(10_000),
+            ..Config::default()
+        };
+        run_with_warnings(input, config)
+    });
+    assert_eq!(
+        Err(LangError::Runtime(EvalError::StepLimitExceeded(10_000))),
+        result
+    );
+}
+
+#[test]
+fn cond_binding() -> LangResult<()> {
+    let input = include_str!("cond_binding.pj");
+    let output = run(input)?;
+    assert_eq!("7\n100\n100\n", output);
+    Ok(())
+}
+
+#[test]
+fn dbg_passes_through() -> LangResult<()> {
+    let input = include_str!("dbg_passes_through.pj");
+    let output = run(input)?;
+    assert_eq!("2\n3\n", output);
+    Ok(())
+}
+
+#[test]
+fn ignore() -> LangResult<()> {
+    let input = include_str!("ignore.pj");
+    let output = run(input)?;
+    assert_eq!("2\n1\n3\n", output);
+    Ok(())
+}
+
+#[test]
+fn many_variables() -> LangResult<()> {
+    // Each binding adds a name to the scope, so the later names are resolved among many others.
+    let mut input = String::from("x0 = 0\n");
+    for i in 1..200 {
+        input.push_str(&format!("x{} = x{} + {}\n", i, i - 1, i % 2));
+    }
+    input.push_str("print(x199 + x0)\n");
+    let output = run(&input)?;
+    assert_eq!("100\n", output);
+    Ok(())
+}
+
+#[test]
+fn cond_block() -> LangResult<()> {
+    let input = include_str!("cond_block.pj");
+    let output = run(input)?;
+    assert_eq!("0\n1\n", output);
+    Ok(())
+}
+
+#[test]
+fn bit_op_overload() -> LangResult<()> {
+    let input = include_str!("bit_op_overload.pj");
+    let output = run(input)?;
+    assert_eq!("2\n7\n0\n1\n1\n0\n", output);
+    Ok(())
+}
+
+#[test]
+fn paren_block() -> LangResult<()> {
+    let input = include_str!("paren_block.pj");
+    let output = run(input)?;
+    assert_eq!("1\n2\n", output);
+    Ok(())
+}
+
+#[test]
+fn seq_keeps_effects() -> LangResult<()> {
+    let input = include_str!("seq_keeps_effects.pj");
+    let output = run(input)?;
+    assert_eq!("1\n2\n", output);
+    Ok(())
+}
+
+#[test]
+fn pow() -> LangResult<()> {
+    let input = include_str!("pow.pj");
+    let output = run(input)?;
+    assert_eq!("4052555153018976267\n512\n-1\n", output);
+    Ok(())
+}
+
+#[test]
+fn print_types() -> LangResult<()> {
+    let input = include_str!("print_types.pj");
+    let config = Config {
+        print_types: true,
+        ..Config::default()
+    };
+    let output = run_with_config(input, config)?;
+    assert_eq!(
+        "1 : Bool\n(λ. _0) : Int -> Int\n3 : Int\ndbg : a -> a\n",
+        output
+    );
+    Ok(())
+}
+
+#[test]
+fn default_args() -> LangResult<()> {
+    let input = include_str!("default_args.pj");
+    let output = run(input)?;
+    assert_eq!("3\n11\n", output);
+    Ok(())
+}
+
+#[test]
+fn deep_recursion() -> LangResult<()> {
+    let input = include_str!("deep_recursion.pj");
+    let output = run(input)?;
+    assert_eq!("50005000\n", output);
+    Ok(())
+}
+
+#[test]
+fn lazy_unused_error() -> LangResult<()> {
+    let input = include_str!("lazy_unused_error.pj");
+    let output = run(input)?;
+    assert_eq!("1\n", output);
+    Ok(())
+}
+
+#[test]
+fn inline() -> LangResult<()> {
+    let input = include_str!("inline.pj");
+    let config = Config {
+        inline: true,
+        ..Config::default()
+    };
+    let output = run_with_config(input, config)?;
+    assert_eq!("42\n7\n11\n", output);
+    assert_eq!(run(input)?, output);
+    Ok(())
+}
+
+#[test]
+fn simplify() -> LangResult<()> {
+    let input = include_str!("simplify.pj");
+    let config = Config {
+        simplify: true,
+        ..Config::default()
+    };
+    let output = run_with_config(input, config)?;
+    assert_eq!("1\n2\n", output);
+    Ok(())
+}
+
+#[test]
+fn print_sign() -> LangResult<()> {
+    let input = include_str!("print_sign.pj");
+    let mut output = Vec::default();
+    let machine = machine_builder(&mut output)
+        .with_print_config(PrintConfig {
+            always_sign: true,
+            ..PrintConfig::default()
+        })
+        .build();
+    run_with_machine(input, machine, Config::default())?;
+    assert_eq!("+5\n+0\n-3\n", String::from_utf8(output).unwrap());
+    assert_eq!("5\n0\n-3\n", run(input)?);
+    Ok(())
+}
+
+#[test]
+fn read_int() -> LangResult<()> {
+    let input = include_str!("read_int.pj");
+    let mut output = Vec::default();
+    let machine = machine_builder(&mut output)
+        .with_input(Cursor::new("42\n"))
+        .build();
+    run_with_machine(input, machine, Config::default())?;
+    assert_eq!("42\n", String::from_utf8(output).unwrap());
+    Ok(())
+}
+
+#[test]
+fn read_int_invalid_input() {
+    let input = include_str!("read_int.pj");
+    let mut output = Vec::default();
+    let machine = machine_builder(&mut output)
+        .with_input(Cursor::new("forty-two\n"))
+        .build();
+    let loc = Location::new(6, 14);
+    assert_eq!(
+        Err(LangError::Runtime(EvalError::InvalidInput { loc })),
+        run_with_machine(input, machine, Config::default())
+    );
+}
+
+#[test]
+fn print_separators() -> LangResult<()> {
+    let input = include_str!("print_separators.pj");
+    let mut output = Vec::default();
+    let machine = machine_builder(&mut output)
+        .with_print_config(PrintConfig {
+            separators: true,
+            ..PrintConfig::default()
+        })
+        .build();
+    run_with_machine(input, machine, Config::default())?;
+    assert_eq!(
+        "3,628,800\n-1,000\n999\n",
+        String::from_utf8(output).unwrap()
+    );
+    assert_eq!("3628800\n-1000\n999\n", run(input)?);
+    Ok(())
+}
+
+#[test]
+fn print_scientific() -> LangResult<()> {
+    let input = include_str!("print_separators.pj");
+    let mut output = Vec::default();
+    let machine = machine_builder(&mut output)
+        .with_print_config(PrintConfig {
+            always_sign: true,
+            scientific_above: Some(999),
+            ..PrintConfig::default()
+        })
+        .build();
+    run_with_machine(input, machine, Config::default())?;
+    assert_eq!(
+        "+3.6288e6\n-1e3\n+999\n",
+        String::from_utf8(output).unwrap()
+    );
+    Ok(())
+}
+
+#[test]
+fn wrapping() -> LangResult<()> {
+    let config = Config {
+        wrapping: true,
+        ..Config::default()
+    };
+    // Programs that do not overflow have the same results.
+    for input in [
+        include_str!("arithmetic.pj"),
+        include_str!("factorial.pj"),
+        include_str!("pow.pj"),
+        include_str!("bit_shift_l.pj"),
+        include_str!("adler32.pj"),
+    ]
+    .iter()
+    {
+        assert_eq!(run(input)?, run_with_warnings(input, config)?.output);
+    }
+    // Programs that overflow wrap around.
+    let input = include_str!("wrapping.pj");
+    let output = run_with_warnings(input, config)?.output;
+    assert_eq!("-9223372036854775808\n-9223372036854775808\n0\n", output);
+    Ok(())
+}
+
+#[test]
+fn buffered_output() -> LangResult<()> {
+    let input = include_str!("print_print.pj");
+    let mut outputs = Vec::new();
+    for &buffered in [false, true].iter() {
+        let mut output = Vec::default();
+        let machine = machine_builder(&mut output)
+            .with_buffered_output(buffered)
+            .build();
+        run_with_machine(input, machine, Config::default())?;
+        outputs.push(String::from_utf8(output).unwrap());
+    }
+    assert_eq!(vec!["10\n0\n"; 2], outputs);
+    Ok(())
+}
+
+#[test]
+fn shadowing_warning() -> LangResult<()> {
+    let input = include_str!("shadowing_warning.pj");
+    let outcome = run_with_warnings(input, Config::default())?;
+    let expected = RunOutcome {
+        output: "11\n".to_owned(),
+        // `x = n + 1` shadows `x = 10`
+        warnings: vec![Warning::Shadowed {
+            name: "x".to_owned(),
+            inner_loc: Location::new(39, 40),
+            outer_loc: Location::new(0, 1),
+        }],
+    };
+    assert_eq!(expected, outcome);
+    Ok(())
+}
+
+#[test]
+fn explicit_rec() -> LangResult<()> {
+    let input = include_str!("explicit_rec.pj");
+    let config = Config {
+        explicit_rec: true,
+        ..Config::default()
+    };
+    let output = run_with_config(input, config)?;
+    assert_eq!("120\n", output);
+    Ok(())
+}
+
+#[test]
+fn explicit_rec_missing() -> LangResult<()> {
+    let input = include_str!("explicit_rec_missing.pj");
+    // Recursion is detected automatically by default.
+    assert_eq!("120\n", run(input)?);
+
+    let config = Config {
+        explicit_rec: true,
+        ..Config::default()
+    };
+    let result = run_with_config(input, config);
+    assert!(
+        matches!(&result, Err(LangError::Ty(TyError::Unbounded(name))) if name.content == "fact"),
+        "{:?}",
+        result
+    );
+    Ok(())
+}
+
+#[test]
+fn compile_once_run_twice() -> LangResult<()> {
+    let input = include_str!("print_print.pj");
+    let program = compile(input, Config::default())?;
+    let mut first = Vec::default();
+    let mut second = Vec::default();
+    program.run(&mut machine_builder(&mut first).build())?;
+    program.run(&mut machine_builder(&mut second).build())?;
+    assert_eq!("10\n0\n", String::from_utf8(first).unwrap());
+    assert_eq!("10\n0\n", String::from_utf8(second).unwrap());
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use std::path::{Path, PathBuf};
+
+use pijama_driver::{load_files, run_file, Config, IncludeErrorKind, LangError, LangResult};
+
+fn path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/include")
+        .join(name)
+}
+
+#[test]
+fn include() -> LangResult<()> {
+    let output = run_file(&path("main.pj"), Config::default())?;
+    assert_eq!("42\n", output);
+    Ok(())
+}
+
+#[test]
+fn include_with_config() -> LangResult<()> {
+    let config = Config {
+        print_types: true,
+        ..Config::default()
+    };
+    let output = run_file(&path("main.pj"), config)?;
+    assert_eq!("42 : Int\n", output);
+    Ok(())
+}
+
+#[test]
+fn include_cycle() {
+    match run_file(&path("cycle_a.pj"), Config::default()).unwrap_err() {
+        LangError::Include(err) => {
+            assert!(
+                matches!(err.kind(), IncludeErrorKind::Cycle(path) if path.ends_with("cycle_a.pj")),
+                "{:?}",
+                err
+            );
+            // The directive closing the cycle is inside `cycle_b.pj`.
+            assert_eq!(1, err.loc().file);
+        }
+        err => panic!("Unexpected error {:?}", err),
+    }
+}
+
+#[test]
+fn included_error() -> LangResult<()> {
+    let err = run_file(&path("included_error.pj"), Config::default()).unwrap_err();
+    let loc = err.loc();
+    let files = load_files(&path("included_error.pj"))?;
+    assert!(files[loc.file].path.ends_with("lib/wrong_type.pj"));
+    assert_eq!("true", &files[loc.file].source[loc.start..loc.end]);
+    Ok(())
+}
+
+#[test]
+fn included_paths() -> LangResult<()> {
+    let files = load_files(&path("main.pj"))?;
+    assert_eq!(path("main.pj"), files[0].path);
+    assert_eq!(
+        path("main.pj").with_file_name("lib/double.pj"),
+        files[1].path
+    );
+    Ok(())
+}
+
+#[test]
+fn include_longer_file() -> LangResult<()> {
+    // Joining the nodes of both files must not build an inverted location.
+    let output = run_file(&path("short.pj"), Config::default())?;
+    assert_eq!("10\n", output);
+    Ok(())
+}
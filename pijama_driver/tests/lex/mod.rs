@@ -0,0 +1,48 @@
+use std::include_str;
+
+use pijama_ast::location::Location;
+
+use pijama_parser::{tokenize, TokenKind};
+
+use pijama_driver::LangResult;
+
+#[test]
+fn fn_def() -> LangResult<()> {
+    let input = include_str!("fn_def.pj");
+    let tokens = tokenize(input)?
+        .into_iter()
+        .map(|token| (token.content.kind(), token.loc))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        vec![
+            (TokenKind::Keyword, Location::new(0, 2)),
+            (TokenKind::Name, Location::new(3, 4)),
+            (TokenKind::Symbol, Location::new(4, 5)),
+            (TokenKind::Name, Location::new(5, 6)),
+            (TokenKind::Symbol, Location::new(6, 7)),
+            (TokenKind::Keyword, Location::new(8, 10)),
+            (TokenKind::Name, Location::new(11, 12)),
+            (TokenKind::Operator, Location::new(13, 14)),
+            (TokenKind::Number, Location::new(15, 16)),
+            (TokenKind::Keyword, Location::new(17, 20)),
+        ],
+        tokens
+    );
+    Ok(())
+}
+
+#[test]
+fn adjacent_ops() -> LangResult<()> {
+    let input = "(<+>) x=-1 2*-3 1==-1 1<+>-1";
+    let tokens = tokenize(input)?
+        .into_iter()
+        .map(|token| token.content.to_string())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        vec!["(", "<+>", ")", "x", "=", "-1", "2", "*", "-3", "1", "==", "-1", "1", "<+>", "-1"],
+        tokens
+    );
+    Ok(())
+}
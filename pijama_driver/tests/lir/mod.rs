@@ -0,0 +1,43 @@
+use std::include_str;
+
+use pijama_ast::{location::Location, node::BinOp};
+use pijama_lir::{ResolveError, Term};
+use pijama_mir::Term as MirTerm;
+use pijama_parser::parse;
+
+use pijama_driver::LangResult;
+
+use crate::{machine_builder, util::DummyLoc};
+
+#[test]
+fn resolve() -> LangResult<()> {
+    let input = include_str!("resolve.pj");
+    let term = Term::from_mir(MirTerm::from_ast(parse(input)?)?)?;
+    // `x` is `_1` and `y` is `_0` inside `add_x`.
+    assert_eq!("((λ. ((λ. (_0 2)) (λ. (_1 + _0)))) 1)", term.to_string());
+    Ok(())
+}
+
+#[test]
+fn resolve_unbound() -> LangResult<()> {
+    let input = include_str!("resolve_unbound.pj");
+    let result = Term::from_mir(MirTerm::from_ast(parse(input)?)?);
+    let error = result.unwrap_err();
+    assert_eq!(ResolveError::Unbound("x".to_owned().loc()), error);
+    // `x` in `x + y`
+    assert_eq!(Location::new(29, 30), ResolveError::loc(&error));
+    Ok(())
+}
+
+#[test]
+fn evaluate_built_term() {
+    // `1 + 1`
+    let term = Term::BinaryOp(
+        BinOp::Add.loc(),
+        Box::new(Term::Lit(1)),
+        Box::new(Term::Lit(1)),
+    );
+    let mut output = Vec::default();
+    let value = machine_builder(&mut output).build().evaluate(term);
+    assert_eq!(Ok(Term::Lit(2)), value);
+}
@@ -0,0 +1,207 @@
+use std::{collections::BTreeSet, include_str};
+
+use pijama_ast::{location::Location, node::Name};
+use pijama_mir::Term;
+use pijama_parser::parse;
+
+use pijama_driver::LangResult;
+
+#[test]
+fn call_locations() -> LangResult<()> {
+    let input = include_str!("call_locations.pj");
+    let term = Term::from_ast(parse(input)?)?;
+    // `f(10, 20)`
+    assert_eq!(Location::new(0, 9), term.loc);
+    match term.content {
+        Term::App(func, _) => {
+            // `f(10`
+            assert_eq!(Location::new(0, 4), func.loc);
+            assert!(matches!(func.content, Term::App(..)));
+        }
+        content => panic!("expected an application, found {:?}", content),
+    }
+    Ok(())
+}
+
+#[test]
+fn size() -> LangResult<()> {
+    let sizes = [
+        ("x", 1),
+        ("1 + 2 * 3", 5),
+        ("if x do 1 else 2 end", 4),
+        ("f(1, 2)", 5),
+    ];
+    for (input, size) in sizes.iter() {
+        let term = Term::from_ast(parse(input)?)?;
+        assert_eq!(*size, term.content.size(), "wrong size for `{}`", input);
+    }
+    Ok(())
+}
+
+#[test]
+fn inline_small_fn() -> LangResult<()> {
+    let input = include_str!("inline_small_fn.pj");
+    let mut term = Term::from_ast(parse(input)?)?;
+    term.content.inline(4);
+    match term.content {
+        Term::Let(_, _, _, tail) => {
+            // `double(21)` is now `let x = 21 in x * 2`
+            assert_eq!("(let x : Int = 21 in (x * 2))", tail.to_string());
+        }
+        content => panic!("expected a let binding, found {:?}", content),
+    }
+    Ok(())
+}
+
+#[test]
+fn inline_only_small_fns() -> LangResult<()> {
+    let input = include_str!("inline_small_fn.pj");
+    let mut term = Term::from_ast(parse(input)?)?;
+    term.content.inline(3);
+    match term.content {
+        Term::Let(_, _, _, tail) => match tail.content {
+            Term::App(func, _) => assert!(matches!(func.content, Term::Var(..))),
+            content => panic!("expected an application, found {:?}", content),
+        },
+        content => panic!("expected a let binding, found {:?}", content),
+    }
+    Ok(())
+}
+
+#[test]
+fn inline_shadowed() -> LangResult<()> {
+    let input = include_str!("inline_shadowed.pj");
+    let mut term = Term::from_ast(parse(input)?)?;
+    term.content.inline(4);
+    match term.content {
+        Term::Let(_, _, _, tail) => match tail.content {
+            Term::Abs(_, _, body) => assert!(matches!(body.content, Term::Var(..))),
+            content => panic!("expected a function, found {:?}", content),
+        },
+        content => panic!("expected a let binding, found {:?}", content),
+    }
+    Ok(())
+}
+
+#[test]
+fn inline_call() -> LangResult<()> {
+    let input = include_str!("inline_call.pj");
+    let mut term = Term::from_ast(parse(input)?)?;
+    term.content.inline(5);
+    match term.content {
+        Term::Let(_, _, _, tail) => {
+            // `add(1, 2)` is now `let x = 1 in let y = 2 in x + y`
+            let expected = Term::from_ast(parse("x: Int = 1\ny: Int = 2\nx + y")?)?;
+            assert!(
+                tail.content.structurally_eq(&expected.content),
+                "expected {}, found {}",
+                expected,
+                tail
+            );
+        }
+        content => panic!("expected a let binding, found {:?}", content),
+    }
+    Ok(())
+}
+
+#[test]
+fn eliminate_common_subterms() -> LangResult<()> {
+    let input = include_str!("cse.pj");
+    let mut term = Term::from_ast(parse(input)?)?;
+    term.content.eliminate_common_subterms();
+    match term.content {
+        Term::Let(_, _, _, tail) => {
+            // The argument of `dbg` is evaluated only if `dbg` is called, so it is not shared.
+            assert_eq!(
+                "(let strict _cse0 = (square 3) in (_cse0 + (_cse0 * (dbg (square 3)))))",
+                tail.to_string()
+            );
+        }
+        content => panic!("expected a let binding, found {:?}", content),
+    }
+    Ok(())
+}
+
+#[test]
+fn eliminate_common_subterms_with_effects() -> LangResult<()> {
+    let mut term = Term::from_ast(parse("dbg(1) + dbg(1)")?)?;
+    let expected = term.clone();
+    term.content.eliminate_common_subterms();
+    assert!(term.content.structurally_eq(&expected.content));
+    Ok(())
+}
+
+#[test]
+fn eliminate_common_subterms_after_effects() -> LangResult<()> {
+    // Sharing `x + 1` would evaluate it before `print(1)`.
+    let mut term = Term::from_ast(parse("print(1)\n(x + 1) + (x + 1)")?)?;
+    let expected = term.clone();
+    term.content.eliminate_common_subterms();
+    assert!(term.content.structurally_eq(&expected.content));
+    Ok(())
+}
+
+#[test]
+fn structurally_eq() -> LangResult<()> {
+    let term = Term::from_ast(parse("f = fn(x: Int) do x + 1 end\nf(2)")?)?;
+    let same = Term::from_ast(parse("f   =   fn(x: Int) do\n    x+1\nend\n\nf(  2  )")?)?;
+    let different = Term::from_ast(parse("f = fn(x: Int) do x + 1 end\nf(3)")?)?;
+    assert!(term.content.structurally_eq(&same.content));
+    assert!(!term.content.structurally_eq(&different.content));
+    Ok(())
+}
+
+#[test]
+fn alpha_eq() -> LangResult<()> {
+    let term = Term::from_ast(parse("fn f(x: Int): Int do x + y end\nf(2)")?)?;
+    let renamed = Term::from_ast(parse("fn g(z: Int): Int do z + y end\ng(2)")?)?;
+    let other_free = Term::from_ast(parse("fn f(x: Int): Int do x + z end\nf(2)")?)?;
+    let captured = Term::from_ast(parse("fn f(y: Int): Int do y + y end\nf(2)")?)?;
+    assert!(
+        term.content.alpha_eq(&renamed.content),
+        "renamed bound variables"
+    );
+    assert!(!term.content.structurally_eq(&renamed.content));
+    assert!(
+        !term.content.alpha_eq(&other_free.content),
+        "different free variables"
+    );
+    assert!(
+        !term.content.alpha_eq(&captured.content),
+        "captured free variable"
+    );
+    Ok(())
+}
+
+#[test]
+fn free_vars_anon_fn() -> LangResult<()> {
+    let input = include_str!("free_vars_anon_fn.pj");
+    let term = Term::from_ast(parse(input)?)?;
+    let expected = vec![Name("y")].into_iter().collect::<BTreeSet<_>>();
+    assert_eq!(expected, term.content.free_vars());
+    Ok(())
+}
+
+#[test]
+fn free_vars_rec_fn() -> LangResult<()> {
+    let input = include_str!("free_vars_rec_fn.pj");
+    let term = Term::from_ast(parse(input)?)?;
+    // `count`, `n` and `w` are bound.
+    let expected = vec![Name("step"), Name("z")]
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+    assert_eq!(expected, term.content.free_vars());
+    Ok(())
+}
+
+#[test]
+fn simplify_cond() -> LangResult<()> {
+    let input = include_str!("simplify_cond.pj");
+    let mut term = Term::from_ast(parse(input)?)?;
+    term.content.simplify();
+    match term.content {
+        Term::Let(_, _, _, tail) => assert_eq!("1", tail.to_string()),
+        content => panic!("expected a let binding, found {:?}", content),
+    }
+    Ok(())
+}
@@ -2,12 +2,19 @@ extern crate pijama_driver;
 
 use std::{panic, sync::mpsc, thread, time::Duration};
 
-use pijama_driver::{run_with_machine, LangResult};
+use pijama_driver::{run_with_machine, Config, LangResult};
 use pijama_machine::{arithmetic::CheckedArithmetic, env::Env, MachineBuilder};
 
+mod annotate;
 mod ast;
+mod check;
 mod eval;
+mod include;
+mod lex;
+mod lir;
+mod mir;
 mod parse;
+mod ty;
 mod type_check;
 mod util;
 
@@ -18,8 +25,12 @@ fn machine_builder(output: &mut Vec<u8>) -> MachineBuilder<&mut Vec<u8>, Checked
 }
 
 fn run(input: &str) -> LangResult<String> {
+    run_with_config(input, Config::default())
+}
+
+fn run_with_config(input: &str, config: Config) -> LangResult<String> {
     let mut output = Vec::default();
-    run_with_machine(input, machine_builder(&mut output).build())?;
+    run_with_machine(input, machine_builder(&mut output).build(), config)?;
     Ok(String::from_utf8(output).unwrap())
 }
 
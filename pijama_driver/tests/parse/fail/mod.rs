@@ -0,0 +1,106 @@
+use std::include_str;
+
+use pijama_ast::location::Location;
+
+use pijama_parser::{parse, ParsingErrorKind};
+
+#[test]
+fn binary_digit() {
+    let input = include_str!("binary_digit.pj");
+    let err = parse(input).unwrap_err();
+    assert_eq!(
+        &ParsingErrorKind::InvalidDigit {
+            digit: '2',
+            base: "binary"
+        },
+        err.kind()
+    );
+    assert_eq!(Location::new(7, 8), err.loc());
+}
+
+#[test]
+fn octal_digit() {
+    let input = include_str!("octal_digit.pj");
+    let err = parse(input).unwrap_err();
+    assert_eq!(
+        &ParsingErrorKind::InvalidDigit {
+            digit: '8',
+            base: "octal"
+        },
+        err.kind()
+    );
+    assert_eq!(Location::new(6, 7), err.loc());
+}
+
+#[test]
+fn hexadecimal_digit() {
+    let input = include_str!("hexadecimal_digit.pj");
+    let err = parse(input).unwrap_err();
+    assert_eq!(
+        &ParsingErrorKind::InvalidDigit {
+            digit: 'G',
+            base: "hexadecimal"
+        },
+        err.kind()
+    );
+    assert_eq!(Location::new(6, 7), err.loc());
+}
+
+#[test]
+fn unexpected_token() {
+    let input = include_str!("unexpected_token.pj");
+    let err = parse(input).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        ParsingErrorKind::UnexpectedToken { .. }
+    ));
+    assert_eq!(Location::new(14, 15), err.loc());
+}
+
+#[test]
+fn trailing_paren() {
+    let input = include_str!("trailing_paren.pj");
+    let err = parse(input).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        ParsingErrorKind::UnexpectedToken { .. }
+    ));
+    assert_eq!(Location::new(6, 7), err.loc());
+}
+
+#[test]
+fn two_holes() {
+    let input = include_str!("two_holes.pj");
+    let err = parse(input).unwrap_err();
+    assert_eq!(
+        &ParsingErrorKind::Custom("Operator sections must have exactly one hole"),
+        err.kind()
+    );
+    assert_eq!(Location::new(4, 5), err.loc());
+}
+
+#[test]
+fn missing_end() {
+    let input = include_str!("missing_end.pj");
+    let err = parse(input).unwrap_err();
+    assert_eq!(
+        &ParsingErrorKind::MissingEnd {
+            opened: Location::new(0, 2)
+        },
+        err.kind()
+    );
+    assert_eq!(Location::new(input.len(), input.len()), err.loc());
+}
+
+#[test]
+fn missing_end_cond_block() {
+    let input = include_str!("missing_end_cond_block.pj");
+    let err = parse(input).unwrap_err();
+    assert_eq!(
+        &ParsingErrorKind::MissingEnd {
+            opened: Location::new(0, 2)
+        },
+        err.kind()
+    );
+    assert_eq!(Location::new(input.len(), input.len()), err.loc());
+}
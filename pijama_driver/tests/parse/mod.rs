@@ -1 +1,2 @@
+mod fail;
 mod pass;
@@ -3,7 +3,8 @@ use std::include_str;
 use pijama_ast::{
     self,
     node::{
-        BinOp::*, Block, Branch, Expression as Expr, Literal, Name, Node, Statement as Stat, UnOp,
+        BinOp::*, Block, Branch, Expression as Expr, Literal, Name, Node, Param, Statement as Stat,
+        UnOp,
     },
     ty::{Ty, TyAnnotation},
 };
@@ -211,6 +212,37 @@ fn unary_op() -> LangResult<()> {
     Ok(())
 }
 
+#[test]
+fn minus() -> LangResult<()> {
+    let input = include_str!("minus.pj");
+    let mut result = block_into_iter(parse(input)?);
+    assert_eq!(
+        Node::Expr(
+            Expr::BinaryOp(
+                Sub,
+                Box::new(Expr::Name(Name("a")).loc()),
+                Box::new(Expr::UnaryOp(UnOp::Neg, Box::new(Expr::Name(Name("b")).loc())).loc()),
+            )
+            .loc(),
+        ),
+        result.next().unwrap(),
+        "negated right operand"
+    );
+    assert_eq!(
+        Node::Expr(
+            Expr::BinaryOp(
+                Sub,
+                Box::new(Expr::UnaryOp(UnOp::Neg, Box::new(Expr::Name(Name("a")).loc())).loc()),
+                Box::new(Expr::Name(Name("b")).loc()),
+            )
+            .loc(),
+        ),
+        result.next().unwrap(),
+        "negated left operand"
+    );
+    Ok(())
+}
+
 #[test]
 fn logic_op() -> LangResult<()> {
     let input = include_str!("logic_op.pj");
@@ -480,6 +512,47 @@ fn cond() -> LangResult<()> {
     );
     Ok(())
 }
+
+#[test]
+fn cond_block() -> LangResult<()> {
+    let input = include_str!("cond_block.pj");
+    let mut result = block_into_iter(parse(input)?);
+    assert_eq!(
+        Node::Expr(
+            Expr::Cond(
+                Branch {
+                    cond: Block {
+                        nodes: vec![Node::Expr(Expr::Name(Name("u")).loc())]
+                            .into_iter()
+                            .collect(),
+                        expr: Box::new(Expr::Name(Name("v")).loc()),
+                    },
+                    body: Block {
+                        nodes: Default::default(),
+                        expr: Box::new(Expr::Name(Name("w")).loc()),
+                    },
+                },
+                vec![Branch {
+                    cond: Block {
+                        nodes: Default::default(),
+                        expr: Box::new(Expr::Name(Name("x")).loc()),
+                    },
+                    body: Block {
+                        nodes: Default::default(),
+                        expr: Box::new(Expr::Name(Name("y")).loc()),
+                    },
+                }],
+                Block {
+                    nodes: Default::default(),
+                    expr: Box::new(Expr::Name(Name("z")).loc()),
+                },
+            )
+            .loc(),
+        ),
+        result.next().unwrap(),
+    );
+    Ok(())
+}
 #[test]
 fn elif() -> LangResult<()> {
     let input = include_str!("elif.pj");
@@ -639,9 +712,12 @@ fn fn_def() -> LangResult<()> {
         Node::Stat(
             Stat::FnDef(
                 Name("foo").loc(),
-                vec![TyAnnotation {
-                    item: Name("x").loc(),
-                    ty: Ty::Int.loc(),
+                vec![Param {
+                    ann: TyAnnotation {
+                        item: Name("x").loc(),
+                        ty: Ty::Int.loc(),
+                    },
+                    default: None,
                 }],
                 TyAnnotation {
                     item: Block {
@@ -650,6 +726,7 @@ fn fn_def() -> LangResult<()> {
                     },
                     ty: Ty::Missing.loc(),
                 },
+                false,
             )
             .loc(),
         ),
@@ -670,6 +747,7 @@ fn fn_def() -> LangResult<()> {
                     },
                     ty: Ty::Unit.loc(),
                 },
+                false,
             )
             .loc(),
         ),
@@ -681,13 +759,19 @@ fn fn_def() -> LangResult<()> {
             Stat::FnDef(
                 Name("foo").loc(),
                 vec![
-                    TyAnnotation {
-                        item: Name("x").loc(),
-                        ty: Ty::Int.loc(),
+                    Param {
+                        ann: TyAnnotation {
+                            item: Name("x").loc(),
+                            ty: Ty::Int.loc(),
+                        },
+                        default: None,
                     },
-                    TyAnnotation {
-                        item: Name("y").loc(),
-                        ty: Ty::Int.loc(),
+                    Param {
+                        ann: TyAnnotation {
+                            item: Name("y").loc(),
+                            ty: Ty::Int.loc(),
+                        },
+                        default: None,
                     },
                 ],
                 TyAnnotation {
@@ -699,6 +783,7 @@ fn fn_def() -> LangResult<()> {
                     },
                     ty: Ty::Missing.loc(),
                 },
+                false,
             )
             .loc(),
         ),
@@ -915,3 +1000,177 @@ fn cmp_and_shift() -> LangResult<()> {
     );
     Ok(())
 }
+
+#[test]
+fn pow() -> LangResult<()> {
+    let input = include_str!("pow.pj");
+    let mut result = block_into_iter(parse(input)?);
+    let b_pow_c = || {
+        Box::new(
+            Expr::BinaryOp(
+                Pow,
+                Box::new(Expr::Name(Name("b")).loc()),
+                Box::new(Expr::Name(Name("c")).loc()),
+            )
+            .loc(),
+        )
+    };
+    assert_eq!(
+        Node::Expr(Expr::BinaryOp(Pow, Box::new(Expr::Name(Name("a")).loc()), b_pow_c()).loc()),
+        result.next().unwrap(),
+        "right associative"
+    );
+    assert_eq!(
+        Node::Expr(Expr::BinaryOp(Mul, Box::new(Expr::Name(Name("a")).loc()), b_pow_c()).loc()),
+        result.next().unwrap(),
+        "pow precedes mul"
+    );
+    Ok(())
+}
+
+#[test]
+fn paren_block() -> LangResult<()> {
+    let input = include_str!("paren_block.pj");
+    let mut result = block_into_iter(parse(input)?);
+    let nodes = vec![
+        Node::Stat(
+            Stat::Assign(
+                TyAnnotation {
+                    item: Name("x").loc(),
+                    ty: Ty::Missing.loc(),
+                },
+                Expr::Literal(Literal::Number(1)).loc(),
+            )
+            .loc(),
+        ),
+        Node::Expr(
+            Expr::Call(
+                Box::new(Expr::Name(Name("f")).loc()),
+                vec![Expr::Name(Name("x")).loc()],
+            )
+            .loc(),
+        ),
+    ];
+    assert_eq!(
+        Node::Expr(
+            Expr::Block(Block {
+                nodes: nodes.into_iter().collect(),
+                expr: Box::new(
+                    Expr::BinaryOp(
+                        Add,
+                        Box::new(Expr::Name(Name("x")).loc()),
+                        Box::new(Expr::Literal(Literal::Number(1)).loc()),
+                    )
+                    .loc()
+                ),
+            })
+            .loc()
+        ),
+        result.next().unwrap(),
+    );
+    Ok(())
+}
+
+#[test]
+fn fn_def_default() -> LangResult<()> {
+    let input = include_str!("fn_def_default.pj");
+    let mut result = block_into_iter(parse(input)?);
+    assert_eq!(
+        Node::Stat(
+            Stat::FnDef(
+                Name("foo").loc(),
+                vec![
+                    Param {
+                        ann: TyAnnotation {
+                            item: Name("x").loc(),
+                            ty: Ty::Int.loc(),
+                        },
+                        default: None,
+                    },
+                    Param {
+                        ann: TyAnnotation {
+                            item: Name("y").loc(),
+                            ty: Ty::Int.loc(),
+                        },
+                        default: Some(Literal::Number(1).loc()),
+                    },
+                ],
+                TyAnnotation {
+                    item: Block {
+                        nodes: Default::default(),
+                        expr: Box::new(Expr::Name(Name("y")).loc()),
+                    },
+                    ty: Ty::Missing.loc(),
+                },
+                false,
+            )
+            .loc(),
+        ),
+        result.next().unwrap(),
+    );
+    Ok(())
+}
+
+#[test]
+fn fn_def_rec() -> LangResult<()> {
+    let input = include_str!("fn_def_rec.pj");
+    let mut result = block_into_iter(parse(input)?);
+    assert_eq!(
+        Node::Stat(
+            Stat::FnDef(
+                Name("foo").loc(),
+                vec![],
+                TyAnnotation {
+                    item: Block {
+                        nodes: Default::default(),
+                        expr: Box::new(
+                            Expr::Call(Box::new(Expr::Name(Name("foo")).loc()), vec![]).loc(),
+                        ),
+                    },
+                    ty: Ty::Unit.loc(),
+                },
+                true,
+            )
+            .loc(),
+        ),
+        result.next().unwrap(),
+    );
+    Ok(())
+}
+
+#[test]
+fn section() -> LangResult<()> {
+    let input = include_str!("section.pj");
+    let mut result = block_into_iter(parse(input)?);
+    let hole = || Box::new(Expr::Name(Name("_hole")).loc());
+    let section = |body| {
+        Node::Expr(
+            Expr::AnonFn(
+                vec![TyAnnotation {
+                    item: Name("_hole").loc(),
+                    ty: Ty::Missing.loc(),
+                }],
+                TyAnnotation {
+                    item: Block {
+                        nodes: Default::default(),
+                        expr: Box::new(body),
+                    },
+                    ty: Ty::Missing.loc(),
+                },
+            )
+            .loc(),
+        )
+    };
+    let number = |n| Box::new(Expr::Literal(Literal::Number(n)).loc());
+    assert_eq!(
+        section(Expr::BinaryOp(Add, hole(), number(1)).loc()),
+        result.next().unwrap(),
+        "hole on the left"
+    );
+    assert_eq!(
+        section(Expr::BinaryOp(Mul, number(2), hole()).loc()),
+        result.next().unwrap(),
+        "hole on the right"
+    );
+    Ok(())
+}
@@ -0,0 +1,232 @@
+use pijama_ast::{
+    location::Location,
+    node::{Literal, Primitive},
+};
+use pijama_ty::{CanonicalError, Ty, TyFolder, TyVisitor};
+
+use pijama_tycheck::{prim_signature, TyError, UnifyWith};
+
+fn arrow(t1: Ty, t2: Ty) -> Ty {
+    Ty::Arrow(Box::new(t1), Box::new(t2))
+}
+
+#[test]
+fn is_arrow() {
+    assert!(arrow(Ty::Int, Ty::Bool).is_arrow());
+    assert!(arrow(arrow(Ty::Int, Ty::Bool), Ty::Unit).is_arrow());
+    assert!(!Ty::Int.is_arrow());
+    assert!(!Ty::Var(0).is_arrow());
+}
+
+#[test]
+fn as_arrow() {
+    let ty = arrow(Ty::Int, arrow(Ty::Bool, Ty::Unit));
+    assert_eq!(Some((&Ty::Int, &arrow(Ty::Bool, Ty::Unit))), ty.as_arrow());
+    assert_eq!(None, Ty::Unit.as_arrow());
+    assert_eq!(None, Ty::Var(0).as_arrow());
+}
+
+#[test]
+fn default_value() {
+    assert_eq!(Some(Literal::Number(0)), Ty::Int.default_value());
+    assert_eq!(Some(Literal::Bool(false)), Ty::Bool.default_value());
+    assert_eq!(Some(Literal::Unit), Ty::Unit.default_value());
+    assert_eq!(None, arrow(Ty::Int, Ty::Int).default_value());
+    assert_eq!(None, Ty::Var(0).default_value());
+}
+
+#[test]
+fn display_normalized() {
+    assert_eq!("a -> a", arrow(Ty::Var(7), Ty::Var(7)).display_normalized());
+    assert_eq!("a -> b", arrow(Ty::Var(2), Ty::Var(5)).display_normalized());
+    assert_eq!(
+        "(a -> Int) -> b -> a",
+        arrow(arrow(Ty::Var(5), Ty::Int), arrow(Ty::Var(2), Ty::Var(5))).display_normalized()
+    );
+}
+
+#[test]
+fn display_normalized_many_vars() {
+    let ty = (0..27)
+        .rev()
+        .fold(Ty::Unit, |ty, index| arrow(Ty::Var(index), ty));
+    assert_eq!(
+        "a -> b -> c -> d -> e -> f -> g -> h -> i -> j -> k -> l -> m -> n -> o -> p -> q -> r \
+         -> s -> t -> u -> v -> w -> x -> y -> z -> a1 -> Unit",
+        ty.display_normalized()
+    );
+}
+
+#[test]
+fn substitute() {
+    let ty = arrow(arrow(Ty::Var(0), Ty::Var(1)), Ty::Var(0));
+    assert_eq!(
+        arrow(arrow(Ty::Int, Ty::Var(1)), Ty::Int),
+        ty.clone().substitute(0, &Ty::Int)
+    );
+    assert_eq!(
+        arrow(arrow(Ty::Var(0), Ty::Var(0)), Ty::Var(0)),
+        ty.clone().substitute(1, &Ty::Var(0))
+    );
+    assert_eq!(ty.clone(), ty.substitute(2, &Ty::Bool));
+}
+
+#[test]
+fn free_vars() {
+    let ty = arrow(arrow(Ty::Var(5), Ty::Int), arrow(Ty::Var(2), Ty::Var(5)));
+    assert_eq!(vec![5, 2], ty.free_vars());
+    assert!(arrow(Ty::Int, Ty::Bool).free_vars().is_empty());
+}
+
+#[test]
+fn custom_folder() {
+    /// Replaces every `Int` by `Bool`.
+    struct IntToBool;
+
+    impl TyFolder for IntToBool {
+        fn fold_ty(&mut self, ty: Ty) -> Ty {
+            match ty {
+                Ty::Int => Ty::Bool,
+                ty => self.super_ty(ty),
+            }
+        }
+    }
+
+    let ty = arrow(arrow(Ty::Int, Ty::Var(0)), Ty::Int);
+    assert_eq!(
+        arrow(arrow(Ty::Bool, Ty::Var(0)), Ty::Bool),
+        IntToBool.fold_ty(ty)
+    );
+}
+
+#[test]
+fn custom_visitor() {
+    /// Counts the `Int`s inside a type.
+    #[derive(Default)]
+    struct CountInts(usize);
+
+    impl TyVisitor for CountInts {
+        fn visit_int(&mut self) {
+            self.0 += 1;
+        }
+    }
+
+    let ty = arrow(arrow(Ty::Int, Ty::Var(0)), arrow(Ty::Bool, Ty::Int));
+    let mut visitor = CountInts::default();
+    visitor.visit_ty(&ty);
+    assert_eq!(2, visitor.0);
+}
+
+#[test]
+fn contains_and_is_concrete() {
+    let ty = arrow(arrow(Ty::Int, Ty::Var(3)), Ty::Bool);
+    assert!(ty.contains(3));
+    assert!(!ty.contains(0));
+    assert!(!ty.is_concrete());
+    assert!(arrow(Ty::Unit, arrow(Ty::Int, Ty::Bool)).is_concrete());
+}
+
+#[test]
+fn arity() {
+    assert_eq!(0, Ty::Int.arity());
+    assert_eq!(0, Ty::Var(0).arity());
+    assert_eq!(2, arrow(Ty::Int, arrow(Ty::Bool, Ty::Unit)).arity());
+    assert_eq!(1, arrow(arrow(Ty::Int, Ty::Bool), Ty::Unit).arity());
+}
+
+#[test]
+fn display() {
+    let ty = arrow(arrow(Ty::Int, Ty::Var(3)), arrow(Ty::Bool, Ty::Unit));
+    assert_eq!("(Int -> ?X3) -> Bool -> Unit", ty.to_string());
+}
+
+#[test]
+fn canonical_round_trip() {
+    let tys = vec![
+        Ty::Bool,
+        Ty::Int,
+        Ty::Unit,
+        Ty::Var(0),
+        Ty::Var(123),
+        arrow(Ty::Int, Ty::Bool),
+        arrow(arrow(Ty::Var(5), Ty::Int), arrow(Ty::Var(12), Ty::Var(5))),
+        arrow(Ty::Var(1), arrow(Ty::Var(10), Ty::Unit)),
+    ];
+    for ty in tys {
+        let canonical = ty.to_canonical();
+        assert_eq!(Ok(ty), Ty::from_canonical(&canonical), "{}", canonical);
+    }
+}
+
+#[test]
+fn canonical_keeps_var_indices() {
+    let ty = arrow(arrow(Ty::Int, Ty::Var(3)), Ty::Bool);
+    assert_eq!("ffiv3b", ty.to_canonical());
+    assert_eq!(
+        Ok(arrow(Ty::Var(7), Ty::Var(7))),
+        Ty::from_canonical("fv7v7")
+    );
+}
+
+#[test]
+fn canonical_errors() {
+    assert_eq!(Err(CanonicalError::UnexpectedEnd), Ty::from_canonical(""));
+    assert_eq!(Err(CanonicalError::UnexpectedEnd), Ty::from_canonical("fi"));
+    assert_eq!(
+        Err(CanonicalError::UnexpectedChar(1, 'x')),
+        Ty::from_canonical("fxi")
+    );
+    assert_eq!(
+        Err(CanonicalError::UnexpectedChar(1, 'i')),
+        Ty::from_canonical("ii")
+    );
+    assert_eq!(
+        Err(CanonicalError::InvalidIndex(1)),
+        Ty::from_canonical("fvi")
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_round_trip() {
+    let ty = arrow(arrow(Ty::Var(5), Ty::Int), arrow(Ty::Bool, Ty::Unit));
+    let json = serde_json::to_string(&ty).unwrap();
+    assert_eq!(ty, serde_json::from_str(&json).unwrap());
+}
+
+#[test]
+fn prim_signatures() {
+    assert_eq!(
+        arrow(Ty::Var(0), Ty::Unit),
+        prim_signature(Primitive::Print)
+    );
+    assert_eq!(
+        arrow(Ty::Var(0), Ty::Var(0)),
+        prim_signature(Primitive::Dbg)
+    );
+    assert_eq!(arrow(Ty::Int, Ty::Int), prim_signature(Primitive::Neg));
+    assert_eq!(arrow(Ty::Bool, Ty::Bool), prim_signature(Primitive::Not));
+    assert_eq!(
+        arrow(Ty::Var(0), Ty::Unit),
+        prim_signature(Primitive::Ignore)
+    );
+    assert_eq!(arrow(Ty::Unit, Ty::Int), prim_signature(Primitive::ReadInt));
+}
+
+#[test]
+fn unify_with() {
+    let ty = arrow(Ty::Var(0), Ty::Int)
+        .unify_with(&arrow(Ty::Bool, Ty::Var(1)), Location::new(0, 1))
+        .unwrap();
+    assert_eq!(arrow(Ty::Bool, Ty::Int), ty);
+}
+
+#[test]
+fn unify_with_mismatch() {
+    let loc = Location::new(3, 7);
+    let result = arrow(Ty::Var(0), Ty::Int).unify_with(&arrow(Ty::Bool, Ty::Bool), loc);
+    match result {
+        Err(TyError::Mismatch { found, .. }) => assert_eq!(loc, found.loc),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
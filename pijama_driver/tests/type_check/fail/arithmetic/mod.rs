@@ -1,4 +1,6 @@
-use crate::{test_type, test_type_for_all_integer_binops, util::DummyLoc};
+use crate::{
+    test_type, test_type_for_all_integer_binops, test_type_with_placeholder, util::DummyLoc,
+};
 
 use pijama_ty::Ty;
 
@@ -10,7 +12,8 @@ test_type!(
     wrong_type_minus,
     Err(LangError::Ty(TyError::Mismatch {
         expected: Ty::Int,
-        found: Ty::Bool.loc()
+        found: Ty::Bool.loc(),
+        hint: None,
     }))
 );
 
@@ -19,17 +22,20 @@ test_type_for_all_integer_binops!(
     mixed_types_placeholder,
     Err(LangError::Ty(TyError::Mismatch {
         expected: Ty::Int,
-        found: Ty::Bool.loc()
+        found: Ty::Bool.loc(),
+        hint: None,
     })),
     OPERATOR
 );
 
-// Test all int binary operators with bool arguments
-test_type_for_all_integer_binops!(
+// Test all int binary operators with bool arguments. `&` and `|` also take bool arguments.
+test_type_with_placeholder!(
     wrong_type_placeholder,
     Err(LangError::Ty(TyError::Mismatch {
         expected: Ty::Int,
-        found: Ty::Bool.loc()
+        found: Ty::Bool.loc(),
+        hint: None,
     })),
-    OPERATOR
+    OPERATOR,
+    /, *, +, -, ^, <<, >>
 );
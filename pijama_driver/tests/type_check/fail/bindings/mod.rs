@@ -10,6 +10,7 @@ test_type!(
     bind_bool_to_int,
     Err(LangError::Ty(TyError::Mismatch {
         expected: Ty::Int,
-        found: Ty::Bool.loc()
+        found: Ty::Bool.loc(),
+        hint: None,
     }))
 );
@@ -1,5 +1,6 @@
 use crate::{
-    test_type_for_all_comparision_binops, test_type_for_all_equality_binops, util::DummyLoc,
+    test_type, test_type_for_all_comparision_binops, test_type_for_all_equality_binops,
+    util::DummyLoc,
 };
 
 use pijama_ty::Ty;
@@ -13,7 +14,8 @@ test_type_for_all_comparision_binops!(
     wrong_type_placeholder,
     Err(LangError::Ty(TyError::Mismatch {
         expected: Ty::Int,
-        found: Ty::Bool.loc()
+        found: Ty::Bool.loc(),
+        hint: None,
     })),
     OPERATOR
 );
@@ -23,7 +25,8 @@ test_type_for_all_equality_binops!(
     mixed_type_int_placeholder,
     Err(LangError::Ty(TyError::Mismatch {
         expected: Ty::Int,
-        found: Ty::Bool.loc()
+        found: Ty::Bool.loc(),
+        hint: None,
     })),
     OPERATOR
 );
@@ -33,7 +36,43 @@ test_type_for_all_equality_binops!(
     mixed_type_bool_placeholder,
     Err(LangError::Ty(TyError::Mismatch {
         expected: Ty::Bool,
-        found: Ty::Int.loc()
+        found: Ty::Int.loc(),
+        hint: None,
     })),
     OPERATOR
 );
+
+// Test all int comparison operators with unit arguments
+test_type_for_all_comparision_binops!(
+    unit_placeholder,
+    Err(LangError::Ty(TyError::NotComparable(Ty::Unit.loc()))),
+    OPERATOR
+);
+
+// Test all int comparison operators with function arguments
+test_type_for_all_comparision_binops!(
+    fn_placeholder,
+    Err(LangError::Ty(TyError::NotComparable(
+        Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int)).loc()
+    ))),
+    OPERATOR
+);
+
+// Every operand of a chain of comparisons must be comparable with its neighbours.
+test_type!(
+    chained_cmp,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Ty::Int,
+        found: Ty::Bool.loc(),
+        hint: None,
+    })),
+    "true"
+);
+
+// Operands are rejected even if they are only known to be functions after unification.
+test_type!(
+    inferred_fn,
+    Err(LangError::Ty(TyError::NotComparable(
+        Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int)).loc()
+    )))
+);
@@ -10,13 +10,19 @@ test_type!(
     wrong_type_cond_input,
     Err(LangError::Ty(TyError::Mismatch {
         expected: Ty::Bool,
-        found: Ty::Int.loc()
+        found: Ty::Int.loc(),
+        hint: Some("conditions must be `Bool`, did you forget a comparison?"),
     }))
 );
 test_type!(
     mixed_types_cond_result,
     Err(LangError::Ty(TyError::Mismatch {
         expected: Ty::Bool,
-        found: Ty::Int.loc()
+        found: Ty::Int.loc(),
+        hint: None,
     }))
 );
+test_type!(
+    cond_binding_in_else,
+    Err(LangError::Ty(TyError::Unbounded("r".to_owned().loc())))
+);
@@ -1,5 +1,7 @@
 use crate::{test_type, util::DummyLoc};
 
+use pijama_ast::location::Location;
+
 use pijama_ty::Ty;
 
 use pijama_tycheck::TyError;
@@ -10,15 +12,18 @@ test_type!(
     wrong_type_fn_call_arg,
     Err(LangError::Ty(TyError::Mismatch {
         expected: Ty::Int,
-        found: Ty::Bool.loc()
-    }))
+        found: Ty::Bool.loc(),
+        hint: None,
+    })),
+    "true"
 );
 
 test_type!(
     wrong_return_type_fn_int_to_int,
     Err(LangError::Ty(TyError::Mismatch {
         expected: Ty::Bool,
-        found: Ty::Int.loc()
+        found: Ty::Int.loc(),
+        hint: None,
     }))
 );
 
@@ -26,7 +31,8 @@ test_type!(
     wrong_type_anon_fn_call_arg,
     Err(LangError::Ty(TyError::Mismatch {
         expected: Ty::Int,
-        found: Ty::Bool.loc()
+        found: Ty::Bool.loc(),
+        hint: None,
     }))
 );
 
@@ -34,7 +40,8 @@ test_type!(
     wrong_type_rec_fn,
     Err(LangError::Ty(TyError::Mismatch {
         expected: Ty::Unit,
-        found: Ty::Int.loc()
+        found: Ty::Int.loc(),
+        hint: None,
     }))
 );
 
@@ -42,6 +49,56 @@ test_type!(
     wrong_type_after_call,
     Err(LangError::Ty(TyError::Mismatch {
         expected: Ty::Bool,
-        found: Ty::Int.loc()
+        found: Ty::Int.loc(),
+        hint: Some("`&&` and `||` only take `Bool`s, did you mean `&` or `|`?"),
     }))
 );
+
+test_type!(
+    empty_fn_with_int_ty,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Ty::Int,
+        found: Ty::Unit.loc(),
+        hint: None,
+    }))
+);
+
+test_type!(
+    wrong_type_default_arg,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Ty::Int,
+        found: Ty::Bool.loc(),
+        hint: None,
+    }))
+);
+
+test_type!(
+    too_many_args,
+    Err(LangError::Ty(TyError::ArityMismatch {
+        expected: 1,
+        found: 2,
+        loc: Location::new(34, 43),
+    })),
+    "foo(1, 2)"
+);
+
+test_type!(
+    checked_too_many_args,
+    Err(LangError::Ty(TyError::ArityMismatch {
+        expected: 1,
+        found: 2,
+        loc: Location::new(34, 41),
+    })),
+    "g(1, 2)"
+);
+
+// Default values are checked even if no call uses them.
+test_type!(
+    unused_wrong_type_default_arg,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Ty::Int,
+        found: Ty::Bool.loc(),
+        hint: None,
+    })),
+    "true"
+);
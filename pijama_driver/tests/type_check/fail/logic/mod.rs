@@ -11,7 +11,8 @@ test_type_for_all_logical_binops!(
     wrong_type_placeholder,
     Err(LangError::Ty(TyError::Mismatch {
         expected: Ty::Bool,
-        found: Ty::Int.loc()
+        found: Ty::Int.loc(),
+        hint: Some("`&&` and `||` only take `Bool`s, did you mean `&` or `|`?"),
     })),
     OPERATOR
 );
@@ -21,7 +22,8 @@ test_type_for_all_logical_binops!(
     mixed_type_placeholder_first_is_bool,
     Err(LangError::Ty(TyError::Mismatch {
         expected: Ty::Bool,
-        found: Ty::Int.loc()
+        found: Ty::Int.loc(),
+        hint: Some("`&&` and `||` only take `Bool`s, did you mean `&` or `|`?"),
     })),
     OPERATOR
 );
@@ -31,7 +33,8 @@ test_type_for_all_logical_binops!(
     mixed_type_placeholder_second_is_bool,
     Err(LangError::Ty(TyError::Mismatch {
         expected: Ty::Bool,
-        found: Ty::Int.loc()
+        found: Ty::Int.loc(),
+        hint: Some("`&&` and `||` only take `Bool`s, did you mean `&` or `|`?"),
     })),
     OPERATOR
 );
@@ -40,6 +43,28 @@ test_type!(
     wrong_type_not,
     Err(LangError::Ty(TyError::Mismatch {
         expected: Ty::Bool,
-        found: Ty::Int.loc()
+        found: Ty::Int.loc(),
+        hint: None,
     }))
 );
+
+// `&` and `|` take either two ints or two bools
+test_type!(
+    mixed_type_bit_op,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Ty::Bool,
+        found: Ty::Int.loc(),
+        hint: None,
+    })),
+    "1"
+);
+
+test_type!(
+    wrong_type_bit_op,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Ty::Int,
+        found: Ty::Unit.loc(),
+        hint: None,
+    })),
+    "unit"
+);
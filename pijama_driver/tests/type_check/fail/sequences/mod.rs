@@ -10,6 +10,17 @@ test_type!(
     int_cannot_be_ignored,
     Err(LangError::Ty(TyError::Mismatch {
         expected: Ty::Unit,
-        found: Ty::Int.loc()
+        found: Ty::Int.loc(),
+        hint: None,
     }))
 );
+
+test_type!(
+    int_in_the_middle_cannot_be_ignored,
+    Err(LangError::Ty(TyError::Mismatch {
+        expected: Ty::Unit,
+        found: Ty::Int.loc(),
+        hint: None,
+    })),
+    "2 + 2"
+);
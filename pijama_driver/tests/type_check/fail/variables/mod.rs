@@ -11,10 +11,17 @@ test_type!(
     Err(LangError::Ty(TyError::Unbounded("x".to_owned().loc())))
 );
 
+test_type!(
+    unbounded_arg,
+    Err(LangError::Ty(TyError::Unbounded("k".to_owned().loc()))),
+    "k"
+);
+
 test_type!(
     shadowing,
     Err(LangError::Ty(TyError::Mismatch {
         expected: Ty::Int,
-        found: Ty::Bool.loc()
+        found: Ty::Bool.loc(),
+        hint: None,
     }))
 );
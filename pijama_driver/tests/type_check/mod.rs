@@ -4,12 +4,13 @@ use pijama_ty::Ty;
 
 use pijama_mir::Term;
 
-use pijama_tycheck::ty_check;
+use pijama_tycheck::{ty_check, ty_check_strict};
 
 use pijama_driver::LangResult;
 
 mod fail;
 mod pass;
+mod strict;
 
 pub fn type_check(input: &str) -> LangResult<Ty> {
     let ast = parse(input)?;
@@ -17,16 +18,37 @@ pub fn type_check(input: &str) -> LangResult<Ty> {
     Ok(ty_check(&mir)?.content)
 }
 
+pub fn type_check_strict(input: &str) -> LangResult<Ty> {
+    let ast = parse(input)?;
+    let mir = Term::from_ast(ast)?;
+    Ok(ty_check_strict(&mir)?.content)
+}
+
 /// Create a test with `$name` that type checks a file with `$name`.pj
 /// in the same directory against the `$pattern`.
+///
+/// If `$marked` is given, the type check must fail and the location of
+/// the error must be the first occurrence of `$marked` in the file.
 #[macro_export]
 macro_rules! test_type {
     ($name:ident, $pattern:expr) => {
         #[test]
         fn $name() {
             let input = include_str!(concat!(stringify!($name), ".pj"));
-            let ty = crate::type_check::type_check(input);
+            let ty = $crate::type_check::type_check(input);
+            assert_eq!(ty, $pattern, "{:#?}", ty);
+        }
+    };
+    ($name:ident, $pattern:expr, $marked:expr) => {
+        #[test]
+        fn $name() {
+            let input = include_str!(concat!(stringify!($name), ".pj"));
+            let ty = $crate::type_check::type_check(input);
             assert_eq!(ty, $pattern, "{:#?}", ty);
+            let start = input.find($marked).expect("marked text not found");
+            let expected = pijama_ast::location::Location::new(start, start + $marked.len());
+            let loc = pijama_driver::LangError::loc(&ty.unwrap_err());
+            assert_eq!(expected, loc, "error not located at `{}`", $marked);
         }
     };
 }
@@ -47,7 +69,7 @@ macro_rules! test_type_with_placeholder {
             ];
             for replacement in &replacements {
                 let input = input.replace(stringify!($placeholder), replacement);
-                let ty = crate::type_check::type_check(&input);
+                let ty = $crate::type_check::type_check(&input);
                 assert_eq!(ty, $pattern,
                     "failed with replacement {}\n{:#?}",
                     replacement, ty);
@@ -62,7 +84,7 @@ macro_rules! test_type_with_placeholder {
 #[macro_export]
 macro_rules! test_type_for_all_integer_binops {
     ($name:ident, $pattern:expr, $placeholder:tt) => {
-        crate::test_type_with_placeholder!(
+        $crate::test_type_with_placeholder!(
             $name,
             $pattern,
             $placeholder,
@@ -76,7 +98,7 @@ macro_rules! test_type_for_all_integer_binops {
 #[macro_export]
 macro_rules! test_type_for_all_comparision_binops {
     ($name:ident, $pattern:expr, $placeholder:tt) => {
-        crate::test_type_with_placeholder!(
+        $crate::test_type_with_placeholder!(
             $name,
             $pattern,
             $placeholder,
@@ -90,7 +112,7 @@ macro_rules! test_type_for_all_comparision_binops {
 #[macro_export]
 macro_rules! test_type_for_all_equality_binops {
     ($name:ident, $pattern:expr, $placeholder:tt) => {
-        crate::test_type_with_placeholder!(
+        $crate::test_type_with_placeholder!(
             $name,
             $pattern,
             $placeholder,
@@ -104,7 +126,7 @@ macro_rules! test_type_for_all_equality_binops {
 #[macro_export]
 macro_rules! test_type_for_all_logical_binops {
     ($name:ident, $pattern:expr, $placeholder:tt) => {
-        crate::test_type_with_placeholder!(
+        $crate::test_type_with_placeholder!(
             $name,
             $pattern,
             $placeholder,
@@ -4,3 +4,4 @@ use pijama_ty::Ty;
 
 test_type!(cond_result_bool_is_bool, Ok(Ty::Bool));
 test_type!(cond_result_int_is_int, Ok(Ty::Int));
+test_type!(empty_cond_is_unit, Ok(Ty::Unit));
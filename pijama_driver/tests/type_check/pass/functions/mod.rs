@@ -19,3 +19,53 @@ test_type!(
     int_function_call_returns_int,
     Ok(Ty::Int)
 );
+test_type!(
+    param_ty_inferred,
+    Ok(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int)))
+);
+test_type!(
+    anon_fn_param_ty_inferred,
+    Ok(Ty::Arrow(Box::new(Ty::Bool), Box::new(Ty::Bool)))
+);
+test_type!(
+    checked_param_ty,
+    Ok(Ty::Arrow(
+        Box::new(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int))),
+        Box::new(Ty::Int)
+    ))
+);
+
+// Recursive functions
+test_type!(
+    rec_fn_inferred_ret_ty,
+    Ok(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int)))
+);
+
+// Primitive functions
+test_type!(
+    neg_is_int_to_int,
+    Ok(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Int)))
+);
+test_type!(
+    not_is_bool_to_bool,
+    Ok(Ty::Arrow(Box::new(Ty::Bool), Box::new(Ty::Bool)))
+);
+test_type!(
+    bin_op_fn,
+    Ok(Ty::Arrow(
+        Box::new(Ty::Int),
+        Box::new(Ty::Arrow(Box::new(Ty::Int), Box::new(Ty::Bool)))
+    ))
+);
+test_type!(
+    dbg_is_polymorphic,
+    Ok(Ty::Arrow(Box::new(Ty::Var(0)), Box::new(Ty::Var(0))))
+);
+test_type!(ignore_in_sequence, Ok(Ty::Int));
+
+// Empty bodies
+test_type!(empty_fn_is_unit, Ok(Ty::Unit));
+test_type!(
+    print_is_polymorphic,
+    Ok(Ty::Arrow(Box::new(Ty::Var(0)), Box::new(Ty::Unit)))
+);
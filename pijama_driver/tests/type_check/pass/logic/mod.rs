@@ -7,3 +7,4 @@ test_type!(not_is_bool, Ok(Ty::Bool));
 // Binary operations
 test_type!(and_is_bool, Ok(Ty::Bool));
 test_type!(or_is_bool, Ok(Ty::Bool));
+test_type!(bit_op_on_bools_is_bool, Ok(Ty::Bool));
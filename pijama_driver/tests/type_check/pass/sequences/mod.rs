@@ -4,3 +4,4 @@ use pijama_ty::Ty;
 
 test_type!(unit_can_be_ignored, Ok(Ty::Bool));
 
+test_type!(print_print_value, Ok(Ty::Int));
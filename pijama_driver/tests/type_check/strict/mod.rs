@@ -0,0 +1,23 @@
+use std::include_str;
+
+use pijama_ty::Ty;
+
+use pijama_tycheck::TyError;
+
+use pijama_driver::LangError;
+
+use crate::{type_check::type_check_strict, util::DummyLoc};
+
+#[test]
+fn ambiguous_print() {
+    let input = include_str!("ambiguous_print.pj");
+    let ty = type_check_strict(input);
+    assert_eq!(
+        ty,
+        Err(LangError::Ty(TyError::AmbiguousType(
+            Ty::Arrow(Box::new(Ty::Var(0)), Box::new(Ty::Unit)).loc()
+        ))),
+        "{:#?}",
+        ty
+    );
+}
@@ -1,12 +1,16 @@
-use std::fmt;
+use std::{collections::HashMap, fmt};
 
 use pijama_ast::{
-    location::Located,
+    location::{Located, Location},
     node::{BinOp, Literal, Primitive, UnOp},
 };
 
+use pijama_ty::Ty;
+
 use Term::*;
 
+pub use lower::{ResolveError, ResolveResult};
+
 mod lower;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -14,12 +18,25 @@ pub enum Term {
     Var(usize),
     Lit(i64),
     Abs(Box<Term>),
-    UnaryOp(UnOp, Box<Term>),
-    BinaryOp(BinOp, Box<Term>, Box<Term>),
+    /// A unary operation. The operator is located at the whole operation, which is used to
+    /// report runtime errors.
+    UnaryOp(Located<UnOp>, Box<Term>),
+    /// A binary operation. The operator is located at the whole operation, which is used to
+    /// report runtime errors.
+    BinaryOp(Located<BinOp>, Box<Term>, Box<Term>),
     App(Box<Term>, Box<Term>),
     Cond(Box<Term>, Box<Term>, Box<Term>),
     Fix(Box<Term>),
-    PrimFn(Primitive),
+    PrimFn(Located<Primitive>),
+    Seq(Box<Term>, Box<Term>),
+    /// A binding whose value is evaluated before it replaces the variable bound by the body.
+    ///
+    /// This is `(λ. t2) t1` with `t1` evaluated before the beta reduction.
+    Let(Box<Term>, Box<Term>),
+    /// The `print` primitive, annotating the printed values with their type.
+    TypedPrint(Ty),
+    /// The body of the function with the given name, which takes the given number of arguments.
+    Named(String, usize, Box<Term>),
 }
 
 impl Term {
@@ -30,6 +47,22 @@ impl Term {
             _ => panic!("Non-boolean literal {}", self),
         }
     }
+
+    /// Returns the name bound to this term if it is a function that was bound to a name.
+    ///
+    /// Functions that have been applied to some of their arguments do not have a name.
+    pub fn fn_name(&self) -> Option<&str> {
+        let mut body = self;
+        let mut params = 0;
+        while let Abs(inner) = body {
+            body = inner;
+            params += 1;
+        }
+        match body {
+            Named(name, arity, _) if *arity == params => Some(name),
+            _ => None,
+        }
+    }
 }
 
 impl From<Literal> for Term {
@@ -72,18 +105,32 @@ impl fmt::Display for Term {
             Cond(t1, t2, t3) => write!(f, "(if {} then {} else {})", t1, t2, t3),
             Fix(t1) => write!(f, "(fix {})", t1),
             PrimFn(prim) => write!(f, "{}", prim),
+            Seq(t1, t2) => write!(f, "({} ; {})", t1, t2),
+            Let(t1, t2) => write!(f, "(let {} in {})", t1, t2),
+            TypedPrint(_) => write!(f, "{}", Primitive::Print),
+            Named(_, _, body) => write!(f, "{}", body),
         }
     }
 }
 
 impl Term {
-    pub fn from_mir(mir: Located<pijama_mir::Term>) -> Self {
-        lower::remove_names(mir)
+    /// Lowers a MIR term, resolving each name to its de Bruijn index.
+    pub fn from_mir(mir: Located<pijama_mir::Term>) -> ResolveResult<Self> {
+        lower::remove_names(mir, HashMap::default())
+    }
+
+    /// Lowers a MIR term replacing each `print` primitive whose location is in `print_tys` by a
+    /// `print` that annotates its output with the corresponding type.
+    pub fn from_mir_with_print_tys(
+        mir: Located<pijama_mir::Term>,
+        print_tys: HashMap<Location, Ty>,
+    ) -> ResolveResult<Self> {
+        lower::remove_names(mir, print_tys)
     }
 
     pub fn shift(&mut self, up: bool, cutoff: usize) {
         match self {
-            Lit(_) | PrimFn(_) => (),
+            Lit(_) | PrimFn(_) | TypedPrint(_) => (),
             Var(index) => {
                 if *index >= cutoff {
                     if up {
@@ -103,7 +150,7 @@ impl Term {
                 t1.shift(up, cutoff);
                 t2.shift(up, cutoff);
             }
-            App(t1, t2) => {
+            App(t1, t2) | Seq(t1, t2) => {
                 t1.shift(up, cutoff);
                 t2.shift(up, cutoff);
             }
@@ -112,7 +159,11 @@ impl Term {
                 t2.shift(up, cutoff);
                 t3.shift(up, cutoff);
             }
-            Fix(t1) => {
+            Let(t1, t2) => {
+                t1.shift(up, cutoff);
+                t2.shift(up, cutoff + 1);
+            }
+            Fix(t1) | Named(_, _, t1) => {
                 t1.shift(up, cutoff);
             }
         }
@@ -120,7 +171,7 @@ impl Term {
 
     pub fn replace(&mut self, index: usize, subs: &mut Term) {
         match self {
-            Lit(_) | PrimFn(_) => (),
+            Lit(_) | PrimFn(_) | TypedPrint(_) => (),
             Var(index2) => {
                 if index == *index2 {
                     *self = subs.clone();
@@ -138,7 +189,7 @@ impl Term {
                 t1.replace(index, subs);
                 t2.replace(index, subs);
             }
-            App(t1, t2) => {
+            App(t1, t2) | Seq(t1, t2) => {
                 t1.replace(index, subs);
                 t2.replace(index, subs);
             }
@@ -147,7 +198,13 @@ impl Term {
                 t2.replace(index, subs);
                 t3.replace(index, subs);
             }
-            Fix(t1) => {
+            Let(t1, t2) => {
+                t1.replace(index, subs);
+                subs.shift(true, 0);
+                t2.replace(index + 1, subs);
+                subs.shift(false, 0);
+            }
+            Fix(t1) | Named(_, _, t1) => {
                 t1.replace(index, subs);
             }
         }
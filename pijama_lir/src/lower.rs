@@ -1,21 +1,60 @@
-use pijama_ast::{location::Located, node::Name};
+use thiserror::Error;
+
+use std::collections::HashMap;
+
+use pijama_ast::{
+    location::{Located, Location},
+    node::{Name, Primitive},
+};
 
 use pijama_mir::{LetKind, Term as MirTerm};
 
+use pijama_ty::Ty;
+
 use crate::Term;
 
-pub fn remove_names(term: Located<MirTerm<'_>>) -> Term {
-    Context::default().remove_names(term.content)
+pub type ResolveResult<T> = Result<T, ResolveError>;
+
+/// An error found while resolving the names of a MIR term.
+#[derive(Error, Debug, Eq, PartialEq)]
+pub enum ResolveError {
+    /// Variant used when a name is not bound by any enclosing abstraction or let binding.
+    #[error("Name `{0}` cannot be resolved")]
+    Unbound(Located<String>),
+}
+
+impl ResolveError {
+    pub fn loc(&self) -> Location {
+        match self {
+            ResolveError::Unbound(name) => name.loc,
+        }
+    }
+}
+
+/// Replaces every name in `term` by its de Bruijn index.
+///
+/// The index of a variable is the number of binders between the variable and the binder of its
+/// name. This function fails if a variable is not bound.
+pub fn remove_names(
+    term: Located<MirTerm<'_>>,
+    print_tys: HashMap<Location, Ty>,
+) -> ResolveResult<Term> {
+    Context {
+        inner: Vec::default(),
+        print_tys,
+    }
+    .remove_names(term)
 }
 
-#[derive(Default)]
 struct Context<'a> {
     inner: Vec<Name<'a>>,
+    /// Types of the values printed by each `print` primitive, indexed by its location.
+    print_tys: HashMap<Location, Ty>,
 }
 
 impl<'a> Context<'a> {
-    fn remove_names(&mut self, term: MirTerm<'a>) -> Term {
-        match term {
+    fn remove_names(&mut self, term: Located<MirTerm<'a>>) -> ResolveResult<Term> {
+        let term = match term.content {
             MirTerm::Lit(lit) => lit.into(),
             MirTerm::Var(name) => {
                 let (index, _) = self
@@ -24,27 +63,29 @@ impl<'a> Context<'a> {
                     .rev()
                     .enumerate()
                     .find(|(_, name2)| name == **name2)
-                    .unwrap();
+                    .ok_or_else(|| {
+                        ResolveError::Unbound(term.loc.with_content(name.0.to_owned()))
+                    })?;
                 Term::Var(index)
             }
             MirTerm::Abs(name, _, body) => {
                 self.inner.push(name);
-                let body = self.remove_names(body.content);
+                let body = self.remove_names(*body)?;
                 self.inner.pop().unwrap();
                 Term::Abs(Box::new(body))
             }
             MirTerm::UnaryOp(op, t1) => {
-                let t1 = self.remove_names(t1.content);
-                Term::UnaryOp(op, Box::new(t1))
+                let t1 = self.remove_names(*t1)?;
+                Term::UnaryOp(term.loc.with_content(op), Box::new(t1))
             }
             MirTerm::BinaryOp(op, t1, t2) => {
-                let t1 = self.remove_names(t1.content);
-                let t2 = self.remove_names(t2.content);
-                Term::BinaryOp(op, Box::new(t1), Box::new(t2))
+                let t1 = self.remove_names(*t1)?;
+                let t2 = self.remove_names(*t2)?;
+                Term::BinaryOp(term.loc.with_content(op), Box::new(t1), Box::new(t2))
             }
             MirTerm::App(t1, t2) => {
-                let t1 = self.remove_names(t1.content);
-                let t2 = self.remove_names(t2.content);
+                let t1 = self.remove_names(*t1)?;
+                let t2 = self.remove_names(*t2)?;
                 Term::App(Box::new(t1), Box::new(t2))
             }
             MirTerm::Let(kind, name, t1, t2) => {
@@ -58,31 +99,61 @@ impl<'a> Context<'a> {
                     // Both things are satisfied by just pushing the name of the function into the
                     // context.
                     self.inner.push(name.content);
-                    Term::Fix(Box::new(Term::Abs(Box::new(self.remove_names(t1.content)))))
+                    let t1 = name_fn(name.content, self.remove_names(*t1)?);
+                    Term::Fix(Box::new(Term::Abs(Box::new(t1))))
                 } else {
                     // if the let binding is non-recursive, we first lower the binded term, and
                     // then we make its name availabe by pushing it into the context
-                    let t1 = self.remove_names(t1.content);
+                    let t1 = name_fn(name.content, self.remove_names(*t1)?);
                     self.inner.push(name.content);
                     t1
                 };
 
-                let t2 = self.remove_names(t2.content);
+                let t2 = self.remove_names(*t2)?;
                 self.inner.pop().unwrap();
-                Term::App(Box::new(Term::Abs(Box::new(t2))), Box::new(t1))
+                if let LetKind::Strict(_) = kind {
+                    Term::Let(Box::new(t1), Box::new(t2))
+                } else {
+                    Term::App(Box::new(Term::Abs(Box::new(t2))), Box::new(t1))
+                }
             }
             MirTerm::Cond(t1, t2, t3) => {
-                let t1 = self.remove_names(t1.content);
-                let t2 = self.remove_names(t2.content);
-                let t3 = self.remove_names(t3.content);
+                let t1 = self.remove_names(*t1)?;
+                let t2 = self.remove_names(*t2)?;
+                let t3 = self.remove_names(*t3)?;
                 Term::Cond(Box::new(t1), Box::new(t2), Box::new(t3))
             }
             MirTerm::Seq(t1, t2) => {
-                let t1 = self.remove_names(t1.content);
-                let t2 = self.remove_names(t2.content);
-                Term::App(Box::new(Term::Abs(Box::new(t2))), Box::new(t1))
+                let t1 = self.remove_names(*t1)?;
+                let t2 = self.remove_names(*t2)?;
+                Term::Seq(Box::new(t1), Box::new(t2))
             }
-            MirTerm::PrimFn(prim) => Term::PrimFn(prim),
-        }
+            MirTerm::PrimFn(Primitive::Print) => match self.print_tys.get(&term.loc) {
+                Some(ty) => Term::TypedPrint(ty.clone()),
+                None => Term::PrimFn(term.loc.with_content(Primitive::Print)),
+            },
+            MirTerm::PrimFn(prim) => Term::PrimFn(term.loc.with_content(prim)),
+        };
+        Ok(term)
+    }
+}
+
+/// Wraps the body of a function in a `Named` term if `term` is a function.
+///
+/// The body is the term inside all the abstractions of the function. This way, the function is
+/// considered to be entered only when all its arguments have been provided.
+fn name_fn(name: Name<'_>, term: Term) -> Term {
+    match term {
+        Term::Abs(body) => Term::Abs(Box::new(name_body(name, 1, *body))),
+        term => term,
+    }
+}
+
+/// Wraps the body of a function in a `Named` term. `params` is the number of abstractions
+/// around `term`.
+fn name_body(name: Name<'_>, params: usize, term: Term) -> Term {
+    match term {
+        Term::Abs(body) => Term::Abs(Box::new(name_body(name, params + 1, *body))),
+        body => Term::Named(name.0.to_owned(), params, Box::new(body)),
     }
 }
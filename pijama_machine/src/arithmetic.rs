@@ -2,23 +2,36 @@ use std::convert::TryFrom;
 
 use pijama_ast::node::{BinOp, BinOp::*, UnOp, UnOp::*};
 
+/// The reason an arithmetic operation failed.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ArithmeticError {
+    /// The operation overflowed and overflows are treated as errors.
+    Overflow,
+    /// The exponent of a power or the amount of a shift is negative.
+    NegativeOperand(i64),
+}
+
 /// Trait determining how arithmetic operations should be handled.
+///
+/// Both methods return `ArithmeticError::Overflow` if the operation overflowed and overflows are
+/// treated as errors.
 pub trait Arithmetic {
-    fn binary_operation(op: BinOp, n1: i64, n2: i64) -> i64;
-    fn unary_operation(op: UnOp, n: i64) -> i64;
+    fn binary_operation(op: BinOp, n1: i64, n2: i64) -> Result<i64, ArithmeticError>;
+    fn unary_operation(op: UnOp, n: i64) -> Result<i64, ArithmeticError>;
 }
 
 /// Regular arithmetic that is allowed to overflow or panic when dividing by zero.
 pub struct OverflowArithmetic;
 
 impl Arithmetic for OverflowArithmetic {
-    fn binary_operation(op: BinOp, n1: i64, n2: i64) -> i64 {
-        match op {
+    fn binary_operation(op: BinOp, n1: i64, n2: i64) -> Result<i64, ArithmeticError> {
+        let result = match op {
             Add => n1 + n2,
             Sub => n1 - n2,
             Mul => n1 * n2,
             Div => n1 / n2,
             Rem => n1 % n2,
+            Pow => pow(n1, n2, |a, b| a * b)?,
             Lt => (n1 < n2).into(),
             Lte => (n1 <= n2).into(),
             Gt => (n1 > n2).into(),
@@ -30,28 +43,80 @@ impl Arithmetic for OverflowArithmetic {
             BitXor => n1 ^ n2,
             Shr => n1 >> n2,
             Shl => n1 << n2,
-        }
+        };
+        Ok(result)
     }
 
-    fn unary_operation(op: UnOp, n: i64) -> i64 {
-        match op {
+    fn unary_operation(op: UnOp, n: i64) -> Result<i64, ArithmeticError> {
+        let result = match op {
             Neg => -n,
             Not => !n,
-        }
+        };
+        Ok(result)
     }
 }
 
-/// Checked arithmetic that panics when overflowing or dividing by zero.
+/// Wrapping arithmetic that never checks for overflows.
+///
+/// Overflowing operations wrap around silently, even in debug builds, so a program can compute
+/// wrong results without any warning. This is the fastest arithmetic and it should only be used
+/// for trusted programs. Dividing by zero still panics.
+pub struct WrappingArithmetic;
+
+impl Arithmetic for WrappingArithmetic {
+    fn binary_operation(op: BinOp, n1: i64, n2: i64) -> Result<i64, ArithmeticError> {
+        let result = match op {
+            Add => n1.wrapping_add(n2),
+            Sub => n1.wrapping_sub(n2),
+            Mul => n1.wrapping_mul(n2),
+            Div => n1.wrapping_div(n2),
+            Rem => n1.wrapping_rem(n2),
+            Pow => pow(n1, n2, i64::wrapping_mul)?,
+            Lt => (n1 < n2).into(),
+            Lte => (n1 <= n2).into(),
+            Gt => (n1 > n2).into(),
+            Gte => (n1 >= n2).into(),
+            Eq => (n1 == n2).into(),
+            Neq => (n1 != n2).into(),
+            BitAnd | And => n1 & n2,
+            BitOr | Or => n1 | n2,
+            BitXor => n1 ^ n2,
+            Shr => n1.wrapping_shr(n2 as u32),
+            Shl => n1.wrapping_shl(n2 as u32),
+        };
+        Ok(result)
+    }
+
+    fn unary_operation(op: UnOp, n: i64) -> Result<i64, ArithmeticError> {
+        let result = match op {
+            Neg => n.wrapping_neg(),
+            Not => !n,
+        };
+        Ok(result)
+    }
+}
+
+/// Checked arithmetic that fails when overflowing or shifting by a negative amount and panics when
+/// dividing by zero.
 pub struct CheckedArithmetic;
 
 impl Arithmetic for CheckedArithmetic {
-    fn binary_operation(op: BinOp, n1: i64, n2: i64) -> i64 {
+    fn binary_operation(op: BinOp, n1: i64, n2: i64) -> Result<i64, ArithmeticError> {
         let (result, overflowed) = match op {
             Add => n1.overflowing_add(n2),
             Sub => n1.overflowing_sub(n2),
             Mul => n1.overflowing_mul(n2),
             Div => n1.overflowing_div(n2),
             Rem => n1.overflowing_rem(n2),
+            Pow => {
+                let mut overflowed = false;
+                let result = pow(n1, n2, |a, b| {
+                    let (result, mul_overflowed) = a.overflowing_mul(b);
+                    overflowed |= mul_overflowed;
+                    result
+                })?;
+                (result, overflowed)
+            }
             Lt => ((n1 < n2).into(), false),
             Lte => ((n1 <= n2).into(), false),
             Gt => ((n1 > n2).into(), false),
@@ -61,37 +126,65 @@ impl Arithmetic for CheckedArithmetic {
             BitAnd | And => (n1 & n2, false),
             BitOr | Or => (n1 | n2, false),
             BitXor => (n1 ^ n2, false),
-            Shr => n1.overflowing_shr(try_into_u32_or_panic(n2)),
-            Shl => n1.overflowing_shl(try_into_u32_or_panic(n2)),
+            Shr => n1.overflowing_shr(try_into_u32(n2)?),
+            Shl => n1.overflowing_shl(try_into_u32(n2)?),
         };
 
         if overflowed {
-            panic!(
-                "Binary operation `{}` overflowed with operands `{}` and `{}`",
-                op, n1, n2
-            )
+            Err(ArithmeticError::Overflow)
+        } else {
+            Ok(result)
         }
-
-        result
     }
 
-    fn unary_operation(op: UnOp, n: i64) -> i64 {
+    fn unary_operation(op: UnOp, n: i64) -> Result<i64, ArithmeticError> {
         let (result, overflowed) = match op {
             Neg => n.overflowing_neg(),
             Not => (!n, false),
         };
 
         if overflowed {
-            panic!("Unary operation `{}` overflowed with operand `{}`", op, n)
+            Err(ArithmeticError::Overflow)
+        } else {
+            Ok(result)
         }
+    }
+}
 
-        result
+/// Computes `base` raised to `exp` using exponentiation by squaring.
+///
+/// Every multiplication is done using `mul` so the caller can decide how to handle overflows. A
+/// multiplication is only done if its result is needed, so if any of them overflows, the final
+/// result overflows too. Negative exponents are an error.
+fn pow(
+    mut base: i64,
+    mut exp: i64,
+    mut mul: impl FnMut(i64, i64) -> i64,
+) -> Result<i64, ArithmeticError> {
+    if exp < 0 {
+        return Err(ArithmeticError::NegativeOperand(exp));
+    }
+
+    let mut result = 1;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul(result, base);
+        }
+        exp >>= 1;
+        if exp > 0 {
+            base = mul(base, base);
+        }
     }
+    Ok(result)
 }
 
-fn try_into_u32_or_panic(n: i64) -> u32 {
-    match u32::try_from(n) {
-        Ok(n) => n,
-        _ => panic!("Operand `{}` is negative", n),
+/// Converts the amount of a shift, which cannot be negative.
+///
+/// Amounts that do not fit in a `u32` are saturated, so shifting by them still overflows.
+fn try_into_u32(n: i64) -> Result<u32, ArithmeticError> {
+    if n < 0 {
+        Err(ArithmeticError::NegativeOperand(n))
+    } else {
+        Ok(u32::try_from(n).unwrap_or(u32::MAX))
     }
 }
@@ -1,14 +1,22 @@
-use std::io::{Stdout, Write};
+use std::io::{BufRead, Stdout, Write};
 
 use crate::{
     arithmetic::{Arithmetic, OverflowArithmetic},
     env::Env,
-    Machine,
+    hook::{Hook, NoHook},
+    Machine, PrintConfig, Stats, Strategy,
 };
 
-pub struct MachineBuilder<W: Write, A: Arithmetic> {
+pub struct MachineBuilder<W: Write, A: Arithmetic, H: Hook = NoHook> {
     env: Env<W>,
     arithmetic: A,
+    hook: H,
+    input: Option<Box<dyn BufRead>>,
+    buffered: bool,
+    max_output_bytes: Option<usize>,
+    print_config: PrintConfig,
+    strategy: Strategy,
+    max_steps: Option<usize>,
 }
 
 impl Default for MachineBuilder<Stdout, OverflowArithmetic> {
@@ -16,29 +24,121 @@ impl Default for MachineBuilder<Stdout, OverflowArithmetic> {
         MachineBuilder {
             env: Env::default(),
             arithmetic: OverflowArithmetic,
+            hook: NoHook,
+            input: None,
+            buffered: false,
+            max_output_bytes: None,
+            print_config: PrintConfig::default(),
+            strategy: Strategy::default(),
+            max_steps: None,
         }
     }
 }
 
-impl<W: Write, A: Arithmetic> MachineBuilder<W, A> {
-    pub fn build(self) -> Machine<W, A> {
+impl<W: Write, A: Arithmetic, H: Hook> MachineBuilder<W, A, H> {
+    pub fn build(mut self) -> Machine<W, A, H> {
+        self.env.set_buffered(self.buffered);
+        self.env.set_max_bytes(self.max_output_bytes);
+        if let Some(input) = self.input {
+            self.env.set_input(input);
+        }
         Machine {
             env: self.env,
             _arithmetic: self.arithmetic,
+            hook: self.hook,
+            stats: Stats::default(),
+            print_config: self.print_config,
+            strategy: self.strategy,
+            max_steps: self.max_steps,
         }
     }
 
-    pub fn with_env<W2: Write>(self, env: Env<W2>) -> MachineBuilder<W2, A> {
+    pub fn with_env<W2: Write>(self, env: Env<W2>) -> MachineBuilder<W2, A, H> {
         MachineBuilder {
             env,
             arithmetic: self.arithmetic,
+            hook: self.hook,
+            input: self.input,
+            buffered: self.buffered,
+            max_output_bytes: self.max_output_bytes,
+            print_config: self.print_config,
+            strategy: self.strategy,
+            max_steps: self.max_steps,
         }
     }
 
-    pub fn with_arithmetic<A2: Arithmetic>(self, arithmetic: A2) -> MachineBuilder<W, A2> {
+    pub fn with_arithmetic<A2: Arithmetic>(self, arithmetic: A2) -> MachineBuilder<W, A2, H> {
         MachineBuilder {
             env: self.env,
             arithmetic,
+            hook: self.hook,
+            input: self.input,
+            buffered: self.buffered,
+            max_output_bytes: self.max_output_bytes,
+            print_config: self.print_config,
+            strategy: self.strategy,
+            max_steps: self.max_steps,
+        }
+    }
+
+    pub fn with_hook<H2: Hook>(self, hook: H2) -> MachineBuilder<W, A, H2> {
+        MachineBuilder {
+            env: self.env,
+            arithmetic: self.arithmetic,
+            hook,
+            input: self.input,
+            buffered: self.buffered,
+            max_output_bytes: self.max_output_bytes,
+            print_config: self.print_config,
+            strategy: self.strategy,
+            max_steps: self.max_steps,
         }
     }
+
+    /// Sets the source from which the machine reads its input. The standard input is used by
+    /// default.
+    pub fn with_input(self, input: impl BufRead + 'static) -> Self {
+        MachineBuilder {
+            input: Some(Box::new(input)),
+            ..self
+        }
+    }
+
+    /// Sets whether the output of the machine is kept in memory until the evaluation ends or
+    /// flushed after every printed line. The output is not buffered by default.
+    ///
+    /// Buffered output is lost if the evaluation panics.
+    pub fn with_buffered_output(self, buffered: bool) -> Self {
+        MachineBuilder { buffered, ..self }
+    }
+
+    /// Sets the maximum number of bytes the machine can print. If printing a value would exceed
+    /// it, the evaluation stops with an error. There is no limit by default.
+    pub fn with_max_output_bytes(self, max_output_bytes: Option<usize>) -> Self {
+        MachineBuilder {
+            max_output_bytes,
+            ..self
+        }
+    }
+
+    /// Sets how the machine prints values. By default, values are printed without any
+    /// formatting.
+    pub fn with_print_config(self, print_config: PrintConfig) -> Self {
+        MachineBuilder {
+            print_config,
+            ..self
+        }
+    }
+
+    /// Sets whether the arguments of functions are evaluated before calling them. By default,
+    /// the evaluation is lazy.
+    pub fn with_strategy(self, strategy: Strategy) -> Self {
+        MachineBuilder { strategy, ..self }
+    }
+
+    /// Sets the maximum number of steps the machine can take. If the evaluation takes more steps,
+    /// it stops with an error. There is no limit by default.
+    pub fn with_max_steps(self, max_steps: Option<usize>) -> Self {
+        MachineBuilder { max_steps, ..self }
+    }
 }
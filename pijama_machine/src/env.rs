@@ -1,21 +1,127 @@
-use std::io::{stdout, Stdout, Write};
+use std::{
+    fmt,
+    io::{self, stdin, stdout, BufRead, Stdout, Write},
+};
 
 pub struct Env<W: Write> {
     stdout: W,
+    /// Source of the lines read by the machine. The standard input is used if this is `None`.
+    stdin: Option<Box<dyn BufRead>>,
+    /// Output that has not been written into `stdout` yet. Only used if the output is buffered.
+    buffer: Option<Vec<u8>>,
+    /// Maximum number of bytes that can be printed.
+    max_bytes: Option<usize>,
+    /// Number of bytes printed so far.
+    printed: usize,
+    /// Whether a line was not printed because it would exceed `max_bytes`.
+    exceeded: bool,
 }
 
 impl<W: Write> Env<W> {
     pub fn new(stdout: W) -> Self {
-        Env { stdout }
+        Env {
+            stdout,
+            stdin: None,
+            buffer: None,
+            max_bytes: None,
+            printed: 0,
+            exceeded: false,
+        }
     }
 
+    /// Returns the underlying writer. Any buffered output has not been written into it yet.
     pub fn stdout(&mut self) -> &mut W {
         &mut self.stdout
     }
+
+    /// Sets whether the output should be kept in memory until `flush` is called or flushed after
+    /// every line.
+    pub fn set_buffered(&mut self, buffered: bool) {
+        if !buffered {
+            // Do not lose or reorder any previous output.
+            self.flush().expect("Flushing the output failed");
+            self.buffer = None;
+        } else if self.buffer.is_none() {
+            self.buffer = Some(Vec::new());
+        }
+    }
+
+    /// Sets the source of the input. The standard input is used by default.
+    pub fn set_input(&mut self, input: Box<dyn BufRead>) {
+        self.stdin = Some(input);
+    }
+
+    /// Sets the maximum number of bytes that can be printed. There is no limit by default.
+    pub fn set_max_bytes(&mut self, max_bytes: Option<usize>) {
+        self.max_bytes = max_bytes;
+    }
+
+    /// Returns the maximum number of bytes that can be printed if a line could not be printed
+    /// because it would exceed it.
+    pub fn exceeded_max_bytes(&self) -> Option<usize> {
+        self.max_bytes.filter(|_| self.exceeded)
+    }
+
+    /// Forgets the bytes printed so far, so the maximum number of bytes applies from scratch.
+    pub fn reset_printed(&mut self) {
+        self.printed = 0;
+        self.exceeded = false;
+    }
+
+    /// Writes a line into the output.
+    ///
+    /// If the output is not buffered, the line is flushed immediately. If the line would exceed
+    /// the maximum number of bytes, it is not written at all.
+    pub fn print(&mut self, args: fmt::Arguments) -> io::Result<()> {
+        let mut line = fmt::format(args);
+        line.push('\n');
+
+        if let Some(max_bytes) = self.max_bytes {
+            if self.printed + line.len() > max_bytes {
+                self.exceeded = true;
+                return Ok(());
+            }
+            self.printed += line.len();
+        }
+
+        match &mut self.buffer {
+            Some(buffer) => buffer.write_all(line.as_bytes()),
+            None => {
+                self.stdout.write_all(line.as_bytes())?;
+                self.stdout.flush()
+            }
+        }
+    }
+
+    /// Reads a line from the input, without its line terminator.
+    ///
+    /// Returns `None` if the input has already ended.
+    pub fn read_line(&mut self) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        let read = match &mut self.stdin {
+            Some(input) => input.read_line(&mut line)?,
+            None => stdin().lock().read_line(&mut line)?,
+        };
+        if read == 0 {
+            return Ok(None);
+        }
+        let len = line.trim_end_matches(&['\n', '\r'][..]).len();
+        line.truncate(len);
+        Ok(Some(line))
+    }
+
+    /// Writes all the buffered output and flushes the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        if let Some(buffer) = &mut self.buffer {
+            self.stdout.write_all(buffer)?;
+            buffer.clear();
+        }
+        self.stdout.flush()
+    }
 }
 
 impl Default for Env<Stdout> {
     fn default() -> Self {
-        Env { stdout: stdout() }
+        Env::new(stdout())
     }
 }
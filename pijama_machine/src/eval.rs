@@ -1,124 +1,303 @@
-use std::{borrow::Borrow, io::Write};
+use std::{
+    fmt::{self, Display, Formatter},
+    io::Write,
+};
 
-use pijama_ast::node::{BinOp, Literal, Primitive, UnOp};
+use pijama_ast::{
+    location::Located,
+    node::{BinOp, Literal, Primitive, UnOp},
+};
 
 use pijama_lir::Term::{self, *};
 
-use crate::{arithmetic::Arithmetic, Machine};
-
-/// Evaluate `$term` in place using the `$self` machine. Then return `(changed, $ret)` where
-/// `changed` states if the evaluation produced any changes and `$ret` is a `Term` (possibly
-/// including `$term`).
-macro_rules! eval_in_place {
-    ($self:ident, $term:ident, $ret:expr) => {{
-        let (changed, new_t) = $self.eval(*$term);
-        *$term = new_t;
-        (changed, $ret)
-    }};
+use pijama_ty::Ty;
+
+use crate::{
+    arithmetic::Arithmetic,
+    hook::{Event, Hook},
+    EvalError, Machine, PrintConfig, Strategy,
+};
+
+/// An evaluation that is waiting for the value of one of its subterms.
+///
+/// Instead of evaluating subterms recursively, the machine pushes a frame into a stack and
+/// evaluates the subterm. When the subterm has been evaluated, the frame is popped and the
+/// evaluation continues using the value of the subterm. This way, the depth of the evaluation is
+/// only bounded by the available memory and not by the size of the native stack.
+enum Frame {
+    /// Waiting for the first operand of a binary operation (_ op t2).
+    BinOpLeft(Located<BinOp>, Box<Term>),
+    /// Waiting for the second operand of a binary operation (n1 op _).
+    BinOpRight(Located<BinOp>, i64),
+    /// Waiting for the operand of a unary operation (op _).
+    UnaryOp(Located<UnOp>),
+    /// Waiting for the function of an application (_ t2).
+    AppFunc(Box<Term>),
+    /// Waiting for the argument of an application ((λ. t1) _). Only used by eager evaluation.
+    AppArg(Box<Term>),
+    /// Waiting for the condition of a conditional (if _ then t2 else t3).
+    Cond(Box<Term>, Box<Term>),
+    /// Waiting for the operand of a fixed-point operation (fix _).
+    Fix,
+    /// Waiting for the first term of a sequence (_ ; t2).
+    Seq(Box<Term>),
+    /// Waiting for the value of a strict binding (let _ in t2).
+    Let(Box<Term>),
+    /// Waiting for the argument of a primitive function (prim _).
+    Prim(Located<Primitive>),
+    /// Waiting for the argument of the typed print primitive (print _).
+    TypedPrint(Ty),
+    /// Waiting for the body of a named function. Only used if the hook is enabled.
+    Exit(String),
+}
+
+/// The state of the evaluation after a single step.
+enum State {
+    /// The term still has to be evaluated.
+    Eval(Term),
+    /// The term cannot be evaluated further. Either because it is a value or because it is stuck.
+    Done(Term),
+    /// The evaluation cannot continue because of an error.
+    Halt(EvalError),
 }
 
-impl<W: Write, A: Arithmetic> Machine<W, A> {
-    pub(super) fn eval(&mut self, mut term: Term) -> (bool, Term) {
-        let mut changed = false;
-        while {
-            let (eval, new_term) = self.step(term);
-            term = new_term;
-            eval
-        } {
-            changed = true;
+impl<W: Write, A: Arithmetic, H: Hook> Machine<W, A, H> {
+    pub(super) fn eval(&mut self, mut term: Term) -> Result<Term, EvalError> {
+        let mut stack = Vec::new();
+        loop {
+            let mut state = self.step(term, &mut stack);
+            self.stats.steps += 1;
+            if let Some(max_steps) = self.max_steps {
+                if self.stats.steps > max_steps {
+                    return Err(EvalError::StepLimitExceeded(max_steps));
+                }
+            }
+            // Keep popping frames while there are evaluated terms.
+            term = loop {
+                self.stats.max_depth = self.stats.max_depth.max(stack.len());
+                match state {
+                    State::Eval(term) => break term,
+                    State::Done(value) => match stack.pop() {
+                        Some(frame) => state = self.resume(frame, value, &mut stack),
+                        None => return Ok(value),
+                    },
+                    State::Halt(error) => return Err(error),
+                }
+            };
         }
-        (changed, term)
     }
 
-    pub(super) fn step(&mut self, term: Term) -> (bool, Term) {
+    /// Evaluation step for any term.
+    ///
+    /// If the term requires the value of a subterm, a frame is pushed into `stack` and the subterm
+    /// is returned to be evaluated.
+    fn step(&mut self, term: Term, stack: &mut Vec<Frame>) -> State {
         match term {
-            // Dispatch step for binary operations
-            BinaryOp(op, t1, t2) => self.step_bin_op(op, t1, t2),
-            // Dispatch step for unary operations
-            UnaryOp(op, t1) => self.step_un_op(op, t1),
-            App(mut t1, arg) => match *t1 {
-                // Dispatch step for beta reduction
-                Abs(body) => self.step_beta_reduction(*body, arg),
+            // Evaluate t1 first and then continue with the binary operation (t1 op t2)
+            BinaryOp(op, t1, t2) => {
+                stack.push(Frame::BinOpLeft(op, t2));
+                State::Eval(*t1)
+            }
+            // Evaluate t1 first and then continue with the unary operation (op t1)
+            UnaryOp(op, t1) => {
+                stack.push(Frame::UnaryOp(op));
+                State::Eval(*t1)
+            }
+            App(t1, arg) => match *t1 {
+                // Evaluate the argument first if the evaluation is eager
+                Abs(body) if self.strategy == Strategy::Eager => {
+                    stack.push(Frame::AppArg(body));
+                    State::Eval(*arg)
+                }
+                // Dispatch step for beta reduction. Arguments that are closed arithmetic
+                // expressions are computed first, so they do not grow with each recursive call.
+                Abs(body) => {
+                    let arg = match fold_closed::<A>(&arg) {
+                        Some(n) => Box::new(Lit(n)),
+                        None => arg,
+                    };
+                    State::Eval(self.step_beta_reduction(*body, arg))
+                }
                 // Dispatch step for primitive application
-                PrimFn(prim) => self.step_primitive_app(prim, *arg),
+                PrimFn(prim) => self.step_primitive_app(prim, *arg, stack),
+                // Evaluate the argument of a typed print before printing it
+                TypedPrint(ty) => {
+                    stack.push(Frame::TypedPrint(ty));
+                    State::Eval(*arg)
+                }
                 // Application with unevaluated first term (t1 t2)
                 // Evaluate t1.
-                _ => eval_in_place!(self, t1, App(t1, arg)),
+                t1 => {
+                    stack.push(Frame::AppFunc(arg));
+                    State::Eval(t1)
+                }
             },
-            // Dispatch step for conditionals
-            Cond(t1, t2, t3) => self.step_cond(t1, t2, t3),
+            // Evaluate t1 first and then continue with the conditional (if t1 then t2 else t3)
+            Cond(t1, t2, t3) => {
+                stack.push(Frame::Cond(t2, t3));
+                State::Eval(*t1)
+            }
             // Dispatch step for fixed point operation
-            Fix(t1) => self.step_fix(t1),
+            Fix(t1) => self.step_fix(t1, stack),
+            // Evaluate t1 first and then continue with t2 (t1 ; t2)
+            Seq(t1, t2) => {
+                stack.push(Frame::Seq(t2));
+                State::Eval(*t1)
+            }
+            // Evaluate t1 first and then continue with the binding (let t1 in t2)
+            Let(t1, t2) => {
+                stack.push(Frame::Let(t2));
+                State::Eval(*t1)
+            }
+            // Evaluate the body of a named function, notifying the hook about it.
+            Named(name, _, body) => {
+                if H::ENABLED {
+                    self.hook.on_event(Event::Enter(&name));
+                    stack.push(Frame::Exit(name));
+                }
+                State::Eval(*body)
+            }
             // Any other term stops the evaluation.
-            Var(_) | Lit(_) | Abs(_) | PrimFn(_) => (false, term),
+            Var(_) | Lit(_) | Abs(_) | PrimFn(_) | TypedPrint(_) => State::Done(term),
         }
     }
-    /// Evaluation step for conditionals (if t1 then t2 else t3)
-    fn step_cond(&mut self, mut t1: Box<Term>, t2: Box<Term>, t3: Box<Term>) -> (bool, Term) {
+
+    /// Continues the evaluation of `frame` using the `value` of its pending subterm.
+    fn resume(&mut self, frame: Frame, value: Term, stack: &mut Vec<Frame>) -> State {
+        match frame {
+            Frame::BinOpLeft(op, t2) => self.resume_bin_op_left(op, value, t2, stack),
+            Frame::BinOpRight(op, n1) => self.resume_bin_op_right(op, n1, value),
+            Frame::UnaryOp(op) => self.resume_un_op(op, value),
+            Frame::AppFunc(arg) => match value {
+                // If the function is now something that can be applied, apply it.
+                Abs(_) | PrimFn(_) | TypedPrint(_) => State::Eval(App(Box::new(value), arg)),
+                // Otherwise the application is stuck.
+                _ => State::Done(App(Box::new(value), arg)),
+            },
+            // Replace the bound variable by the value of the argument inside the body.
+            Frame::AppArg(body) => State::Eval(self.step_beta_reduction(*body, Box::new(value))),
+            Frame::Cond(t2, t3) => self.resume_cond(value, t2, t3),
+            Frame::Fix => match value {
+                // If the operand is now an abstraction, take the fixed point.
+                Abs(_) => State::Eval(Fix(Box::new(value))),
+                // Otherwise the fixed-point operation is stuck.
+                _ => State::Done(Fix(Box::new(value))),
+            },
+            // Discard the value of t1 and evaluate to t2.
+            Frame::Seq(t2) => State::Eval(*t2),
+            // Replace the bound variable by the value of t1 inside t2.
+            Frame::Let(t2) => State::Eval(self.step_beta_reduction(*t2, Box::new(value))),
+            Frame::Prim(prim) => self.resume_primitive_app(prim, value),
+            Frame::TypedPrint(ty) => {
+                self.hook.on_event(Event::Print(&value));
+                let value = Printed(&value, self.print_config);
+                self.env
+                    .print(format_args!("{} : {}", value, ty.display_normalized()))
+                    .expect("Primitive print failed");
+                self.printed(Literal::Unit.into())
+            }
+            Frame::Exit(name) => {
+                self.hook.on_event(Event::Exit(&name));
+                State::Done(value)
+            }
+        }
+    }
+
+    /// Finishes an evaluation that printed a value, unless the output exceeded its maximum
+    /// number of bytes.
+    fn printed(&self, value: Term) -> State {
+        match self.env.exceeded_max_bytes() {
+            Some(max_bytes) => State::Halt(EvalError::OutputLimitExceeded(max_bytes)),
+            None => State::Done(value),
+        }
+    }
+
+    /// Continuation for conditionals (if t1 then t2 else t3) once t1 has been evaluated
+    fn resume_cond(&mut self, t1: Term, t2: Box<Term>, t3: Box<Term>) -> State {
         // If t1 is a literal, we should be able to evaluate the conditional
-        if let lit @ Term::Lit(_) = t1.borrow() {
+        if let lit @ Term::Lit(_) = &t1 {
             if lit.as_bool() {
                 // If t1 is true, evaluate to t2.
-                (true, *t2)
+                State::Eval(*t2)
             } else {
                 // If t1 is false, evaluate to t3.
-                (true, *t3)
+                State::Eval(*t3)
             }
         } else {
-            // If t1 is not a literal, evaluate it in place and return (if t1 then t2 else t3)
-            eval_in_place!(self, t1, Term::Cond(t1, t2, t3))
+            // If t1 is not a literal, the conditional is stuck.
+            State::Done(Term::Cond(Box::new(t1), t2, t3))
         }
     }
 
-    /// Evaluation step for binary operations (t1 op t2)
-    fn step_bin_op(&mut self, op: BinOp, mut t1: Box<Term>, mut t2: Box<Term>) -> (bool, Term) {
+    /// Continuation for binary operations (t1 op t2) once t1 has been evaluated
+    fn resume_bin_op_left(
+        &mut self,
+        op: Located<BinOp>,
+        t1: Term,
+        t2: Box<Term>,
+        stack: &mut Vec<Frame>,
+    ) -> State {
         use BinOp::*;
 
-        match (op, t1.borrow(), t2.borrow()) {
+        match (op.content, &t1) {
             // If op is && and t1 is false evaluate to false
-            (And, Lit(0), _) => (true, false.into()),
+            (And, Lit(0)) => State::Done(false.into()),
             // If op is || and t1 is true evaluate to true
-            (Or, Lit(1), _) => (true, true.into()),
+            (Or, Lit(1)) => State::Done(true.into()),
+            // If t1 is a literal, evaluate t2.
+            (_, Lit(n1)) => {
+                stack.push(Frame::BinOpRight(op, *n1));
+                State::Eval(*t2)
+            }
+            // If t1 is not a literal, the operation is stuck.
+            _ => State::Done(Term::BinaryOp(op, Box::new(t1), t2)),
+        }
+    }
+
+    /// Continuation for binary operations (n1 op t2) once t2 has been evaluated
+    fn resume_bin_op_right(&mut self, op: Located<BinOp>, n1: i64, t2: Term) -> State {
+        match t2 {
             // If both are literals evaluate with native operation
-            (_, Lit(l1), Lit(l2)) => (true, Lit(A::binary_operation(op, *l1, *l2))),
-            // If t2 is not a literal, evaluate it.
-            (_, Lit(_), _) => {
-                let (changed, new_t2) = self.eval(*t2);
-                *t2 = new_t2;
-                (changed, Term::BinaryOp(op, t1, t2))
-            }
-            // If t1 is not a literal, evaluate it.
-            _ => eval_in_place!(self, t1, Term::BinaryOp(op, t1, t2)),
+            Lit(n2) => match A::binary_operation(op.content, n1, n2) {
+                Ok(n) => State::Done(Lit(n)),
+                Err(err) => State::Halt(EvalError::from_arithmetic(err, op.loc)),
+            },
+            // If t2 is not a literal, the operation is stuck.
+            _ => State::Done(Term::BinaryOp(op, Box::new(Lit(n1)), Box::new(t2))),
         }
     }
 
-    /// Evaluation step for unary operations (op t1)
-    fn step_un_op(&mut self, op: UnOp, mut t1: Box<Term>) -> (bool, Term) {
-        // If t1 is a literal, do the operation.
-        if let Term::Lit(lit) = t1.borrow() {
-            (true, Term::Lit(A::unary_operation(op, *lit)))
-        // If t1 is not a literal, evaluate it.
-        } else {
-            eval_in_place!(self, t1, Term::UnaryOp(op, t1))
+    /// Continuation for unary operations (op t1) once t1 has been evaluated
+    fn resume_un_op(&mut self, op: Located<UnOp>, t1: Term) -> State {
+        match t1 {
+            // If t1 is a literal, do the operation.
+            Lit(n) => match A::unary_operation(op.content, n) {
+                Ok(n) => State::Done(Lit(n)),
+                Err(err) => State::Halt(EvalError::from_arithmetic(err, op.loc)),
+            },
+            // If t1 is not a literal, the operation is stuck.
+            _ => State::Done(Term::UnaryOp(op, Box::new(t1))),
         }
     }
 
     /// Evaluation step for the fixed-point operation (fix t1)
-    fn step_fix(&mut self, mut t1: Box<Term>) -> (bool, Term) {
+    fn step_fix(&mut self, t1: Box<Term>, stack: &mut Vec<Frame>) -> State {
         // If t1 is an abstraction (\. t2), replace the argument of t1 by (fix t1) inside t2
         // and evaluate to t2.
-        if let Term::Abs(t2) = t1.borrow() {
+        if let Term::Abs(t2) = t1.as_ref() {
             let mut t2 = t2.clone();
             t2.replace(0, &mut Term::Fix(t1));
-            (true, *t2)
+            State::Eval(*t2)
         // If t1 is not an abstraction, evaluate it.
         } else {
-            eval_in_place!(self, t1, Term::Fix(t1))
+            stack.push(Frame::Fix);
+            State::Eval(*t1)
         }
     }
 
     /// Evaluation step for beta reduction ((λ. body) arg)
-    fn step_beta_reduction(&mut self, mut body: Term, mut arg: Box<Term>) -> (bool, Term) {
+    fn step_beta_reduction(&mut self, mut body: Term, mut arg: Box<Term>) -> Term {
+        self.hook.on_event(Event::Reduction);
         // increase the indices of the argument so they can coincide with the indices of the body.
         arg.shift(true, 0);
         // replace the index 0 by the argument inside the body.
@@ -127,17 +306,128 @@ impl<W: Write, A: Arithmetic> Machine<W, A> {
         // longer exists.
         body.shift(false, 0);
         // return the body
-        (true, body)
+        body
     }
+
     /// Evaluation step for application of primitive functions (prim arg)
-    fn step_primitive_app(&mut self, prim: Primitive, arg: Term) -> (bool, Term) {
-        match prim {
+    fn step_primitive_app(
+        &mut self,
+        prim: Located<Primitive>,
+        arg: Term,
+        stack: &mut Vec<Frame>,
+    ) -> State {
+        match prim.content {
+            // Negations are evaluated exactly like their unary operators.
+            Primitive::Neg => State::Eval(UnaryOp(prim.loc.with_content(UnOp::Neg), Box::new(arg))),
+            Primitive::Not => State::Eval(UnaryOp(prim.loc.with_content(UnOp::Not), Box::new(arg))),
+            // Any other primitive requires its argument to be evaluated first.
+            Primitive::Print | Primitive::Dbg | Primitive::Ignore | Primitive::ReadInt => {
+                stack.push(Frame::Prim(prim));
+                State::Eval(arg)
+            }
+        }
+    }
+
+    /// Continuation for application of primitive functions (prim arg) once arg has been evaluated
+    fn resume_primitive_app(&mut self, prim: Located<Primitive>, arg: Term) -> State {
+        match prim.content {
             Primitive::Print => {
-                // Evaluate argument
-                let (_, arg) = self.eval(arg);
-                writeln!(self.env.stdout(), "{}", arg).expect("Primitive print failed");
-                (true, Literal::Unit.into())
+                self.hook.on_event(Event::Print(&arg));
+                self.env
+                    .print(format_args!("{}", Printed(&arg, self.print_config)))
+                    .expect("Primitive print failed");
+                self.printed(Literal::Unit.into())
+            }
+            Primitive::Dbg => {
+                // Return the argument after printing it
+                self.hook.on_event(Event::Print(&arg));
+                self.env
+                    .print(format_args!("{}", Printed(&arg, self.print_config)))
+                    .expect("Primitive dbg failed");
+                self.printed(arg)
+            }
+            // The argument has already been evaluated for its effects, only its value is left.
+            Primitive::Ignore => State::Done(Literal::Unit.into()),
+            Primitive::ReadInt => {
+                let line = self.env.read_line().expect("Primitive read_int failed");
+                match line.and_then(|line| line.trim().parse::<i64>().ok()) {
+                    Some(n) => State::Done(Lit(n)),
+                    None => State::Halt(EvalError::InvalidInput { loc: prim.loc }),
+                }
+            }
+            Primitive::Neg | Primitive::Not => {
+                unreachable!("`{}` does not wait for its argument", prim.content)
+            }
+        }
+    }
+}
+
+/// A value formatted according to a `PrintConfig`.
+struct Printed<'a>(&'a Term, PrintConfig);
+
+impl<'a> Display for Printed<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Printed(Lit(n), config) => {
+                let sign = if *n < 0 {
+                    "-"
+                } else if config.always_sign {
+                    "+"
+                } else {
+                    ""
+                };
+                let abs = n.unsigned_abs();
+                if config.scientific_above.is_some_and(|max| abs > max) {
+                    write!(f, "{}{:e}", sign, abs)
+                } else if config.separators {
+                    write!(f, "{}{}", sign, separated(abs))
+                } else {
+                    write!(f, "{}{}", sign, abs)
+                }
             }
+            // Functions bound to a name are printed as that name.
+            Printed(term, _) => match term.fn_name() {
+                Some(name) => write!(f, "{}", name),
+                None => write!(f, "{}", term),
+            },
+        }
+    }
+}
+
+/// Computes the value of `term` if it only has literals and operations over them.
+///
+/// Computing such a term has no effects and always terminates, so it can be done before the term
+/// is passed lazily to a function. `None` is returned if the term has anything else or if
+/// computing it would fail, so the failure only happens if the term is actually used.
+fn fold_closed<A: Arithmetic>(term: &Term) -> Option<i64> {
+    match term {
+        Lit(n) => Some(*n),
+        UnaryOp(op, t1) => A::unary_operation(op.content, fold_closed::<A>(t1)?).ok(),
+        BinaryOp(op, t1, t2) => {
+            let n1 = fold_closed::<A>(t1)?;
+            match (op.content, n1) {
+                (BinOp::And, 0) => Some(0),
+                (BinOp::Or, 1) => Some(1),
+                _ => match (op.content, fold_closed::<A>(t2)?) {
+                    // Dividing by zero panics, which must not happen if the term is never used.
+                    (BinOp::Div, 0) | (BinOp::Rem, 0) => None,
+                    (op, n2) => A::binary_operation(op, n1, n2).ok(),
+                },
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns `n` with its digits separated in groups of three by commas.
+fn separated(n: u64) -> String {
+    let digits = n.to_string();
+    let mut output = String::with_capacity(digits.len() * 4 / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            output.push(',');
         }
+        output.push(digit);
     }
+    output
 }
@@ -0,0 +1,39 @@
+//! Instrumentation of the evaluation.
+use pijama_lir::Term;
+
+/// An event happening during the evaluation of a term.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event<'a> {
+    /// The evaluation of the body of a named function starts.
+    Enter(&'a str),
+    /// The evaluation of the body of a named function finished.
+    Exit(&'a str),
+    /// A value is printed.
+    Print(&'a Term),
+    /// A function is applied to its argument.
+    Reduction,
+}
+
+/// Trait determining what to do with the events happening during evaluation.
+pub trait Hook {
+    /// States if the hook observes events at all. The machine skips the bookkeeping required to
+    /// emit `Exit` events if this is `false`.
+    const ENABLED: bool = true;
+
+    fn on_event(&mut self, event: Event<'_>);
+}
+
+/// Hook that ignores all the events.
+pub struct NoHook;
+
+impl Hook for NoHook {
+    const ENABLED: bool = false;
+
+    fn on_event(&mut self, _event: Event<'_>) {}
+}
+
+impl<F: FnMut(Event<'_>)> Hook for F {
+    fn on_event(&mut self, event: Event<'_>) {
+        self(event)
+    }
+}
@@ -1,23 +1,130 @@
+use thiserror::Error;
+
 use std::io::Write;
 
+use pijama_ast::location::Location;
+
 use pijama_lir::Term;
 
-use crate::{arithmetic::Arithmetic, env::Env};
+use crate::{
+    arithmetic::{Arithmetic, ArithmeticError},
+    env::Env,
+    hook::{Hook, NoHook},
+};
 
 pub mod arithmetic;
 mod builder;
 pub mod env;
 mod eval;
+pub mod hook;
 
 pub use builder::MachineBuilder;
 
-pub struct Machine<W: Write, A: Arithmetic> {
+/// Statistics about the last evaluation done by a machine.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    /// Number of evaluation steps taken.
+    pub steps: usize,
+    /// Maximum number of pending evaluations, or frames, at any point of the evaluation.
+    pub max_depth: usize,
+}
+
+/// Options controlling how values are printed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PrintConfig {
+    /// Print a `+` sign before non-negative integers.
+    pub always_sign: bool,
+    /// Separate the digits of integers in groups of three with commas.
+    pub separators: bool,
+    /// Print integers whose absolute value is larger than this in scientific notation.
+    pub scientific_above: Option<u64>,
+}
+
+/// The order in which the arguments of functions are evaluated.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Arguments are passed to functions without evaluating them. They are evaluated each time
+    /// they are used, if they are used at all.
+    ///
+    /// Arguments made only of literals and operations over them are computed before they are
+    /// passed, which cannot be told apart from computing them later but keeps recursive calls like
+    /// `f(n - 1)` from building longer and longer arguments.
+    #[default]
+    Lazy,
+    /// Arguments are evaluated before they are passed to functions.
+    Eager,
+}
+
+/// An error that stops the evaluation before the term becomes a value.
+#[derive(Error, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum EvalError {
+    #[error("The output exceeded the limit of {0} bytes")]
+    OutputLimitExceeded(usize),
+    #[error("The evaluation exceeded the limit of {0} steps")]
+    StepLimitExceeded(usize),
+    #[error("Integer overflow")]
+    Overflow { loc: Location },
+    #[error("Operand `{value}` is negative")]
+    NegativeOperand { value: i64, loc: Location },
+    #[error("The input is not an integer")]
+    InvalidInput { loc: Location },
+}
+
+impl EvalError {
+    /// Returns the location of the code causing the error.
+    ///
+    /// Errors that are not caused by a particular part of the code have an empty location at the
+    /// start of the input.
+    pub fn loc(&self) -> Location {
+        match self {
+            EvalError::OutputLimitExceeded(_) | EvalError::StepLimitExceeded(_) => {
+                Location::new(0, 0)
+            }
+            EvalError::Overflow { loc }
+            | EvalError::NegativeOperand { loc, .. }
+            | EvalError::InvalidInput { loc } => *loc,
+        }
+    }
+
+    /// Creates the error for an arithmetic operation at `loc` that failed.
+    fn from_arithmetic(err: ArithmeticError, loc: Location) -> Self {
+        match err {
+            ArithmeticError::Overflow => EvalError::Overflow { loc },
+            ArithmeticError::NegativeOperand(value) => EvalError::NegativeOperand { value, loc },
+        }
+    }
+}
+
+pub struct Machine<W: Write, A: Arithmetic, H: Hook = NoHook> {
     env: Env<W>,
     _arithmetic: A,
+    hook: H,
+    stats: Stats,
+    print_config: PrintConfig,
+    strategy: Strategy,
+    max_steps: Option<usize>,
 }
 
-impl<W: Write, A: Arithmetic> Machine<W, A> {
-    pub fn evaluate(&mut self, term: Term) -> Term {
-        self.eval(term).1
+impl<W: Write, A: Arithmetic, H: Hook> Machine<W, A, H> {
+    /// Evaluates `term` and returns its value.
+    ///
+    /// The term does not need to come from a program: it can be built directly. However, it is
+    /// not type-checked, so evaluating an ill-typed term returns the term where the evaluation
+    /// got stuck.
+    ///
+    /// The evaluation stops with an error if printing a value would exceed the maximum number
+    /// of bytes of the output, if it takes more steps than the maximum allowed, if an integer
+    /// operation overflows or if `read_int` does not find an integer in the input.
+    pub fn evaluate(&mut self, term: Term) -> Result<Term, EvalError> {
+        self.stats = Stats::default();
+        self.env.reset_printed();
+        let result = self.eval(term);
+        self.env.flush().expect("Flushing the output failed");
+        result
+    }
+
+    /// Returns the statistics of the last evaluation.
+    pub fn stats(&self) -> Stats {
+        self.stats
     }
 }
@@ -0,0 +1,319 @@
+//! Elimination of common subterms.
+//!
+//! This pass finds pure subterms that appear more than once in the same region of a term and
+//! binds them to a name, so they are evaluated only once. A region is a part of a term whose
+//! subterms are always evaluated when the region is evaluated: the operands of the operators,
+//! the function of an application and the condition of a conditional. Any other subterm, like a
+//! branch of a conditional, the argument of an application or the body of a function or a let
+//! binding, starts a new region.
+//!
+//! The repeated subterms of a region are bound with strict let bindings at the start of the
+//! region. A subterm is pure if evaluating it cannot print or read anything, so evaluating it
+//! only once does not change the output of the program. However, a pure subterm can still fail or
+//! loop forever, so it is only shared if every subterm evaluated before its first use in the
+//! region is inert: evaluating an inert subterm cannot fail, loop or have any effect.
+use pijama_ast::{
+    location::{Located, Location},
+    node::{BinOp, Literal, Name, Primitive},
+};
+
+use crate::{LetKind, Term};
+
+/// Names of the bindings introduced by this pass. They start with an underscore, so they cannot
+/// shadow or be shadowed by any name in the program.
+///
+/// Each region binds at most one subterm per name. The regions nested inside a region never use
+/// the names bound by it, so they can bind the same names again.
+const SHARED: [&str; 4] = ["_cse0", "_cse1", "_cse2", "_cse3"];
+
+/// A name in scope and what is known about the effects of using it.
+struct Binding<'a> {
+    name: Name<'a>,
+    /// Evaluating the name has no effects.
+    pure_value: bool,
+    /// Calling the name with pure arguments has no effects.
+    pure_call: bool,
+    /// Evaluating the name only reads a value that was already computed.
+    inert: bool,
+}
+
+impl<'a> Term<'a> {
+    /// Binds every pure subterm that is repeated inside a region to a name, so it is evaluated
+    /// only once.
+    ///
+    /// Only applications of primitives without effects and of let-bound functions whose bodies
+    /// are pure can be shared. The parameters of functions are never assumed to be pure because
+    /// they can be bound to any term.
+    pub fn eliminate_common_subterms(&mut self) {
+        share_region(self, &mut Vec::new());
+    }
+}
+
+/// Binds the repeated pure subterms of the region starting at `term` and does the same for the
+/// regions nested inside it.
+fn share_region<'a>(term: &mut Term<'a>, scope: &mut Vec<Binding<'a>>) {
+    let mut bindings = Vec::new();
+    for name in SHARED.iter().map(|name| Name(name)) {
+        let shared = match repeated_subterm(term, scope) {
+            Some(shared) => shared,
+            None => break,
+        };
+        let mut locs = Vec::new();
+        replace(term, &shared.content, name, &mut locs);
+        // The body of the binding spans all the uses of the name.
+        let body_loc = locs[0] + locs[locs.len() - 1];
+        bindings.push((name, shared, body_loc));
+    }
+
+    share_nested(term, scope);
+
+    for (name, shared, body_loc) in bindings.into_iter().rev() {
+        let body = std::mem::replace(term, Term::Lit(Literal::Unit));
+        *term = Term::Let(
+            LetKind::Strict(None),
+            shared.loc.with_content(name),
+            Box::new(shared),
+            Box::new(body_loc.with_content(body)),
+        );
+    }
+}
+
+/// Calls `share_region` on every region nested inside the region of `term`.
+fn share_nested<'a>(term: &mut Term<'a>, scope: &mut Vec<Binding<'a>>) {
+    match term {
+        Term::Var(_) | Term::Lit(_) | Term::PrimFn(_) => (),
+        Term::Abs(name, _, body) => {
+            scope.push(Binding {
+                name: *name,
+                pure_value: false,
+                pure_call: false,
+                inert: false,
+            });
+            share_region(&mut body.content, scope);
+            scope.pop();
+        }
+        Term::UnaryOp(_, t1) => share_nested(&mut t1.content, scope),
+        // The second operand of a logical operator is not evaluated if the first one decides
+        // the result.
+        Term::BinaryOp(BinOp::And, t1, t2) | Term::BinaryOp(BinOp::Or, t1, t2) => {
+            share_nested(&mut t1.content, scope);
+            share_region(&mut t2.content, scope);
+        }
+        Term::BinaryOp(_, t1, t2) | Term::Seq(t1, t2) => {
+            share_nested(&mut t1.content, scope);
+            share_nested(&mut t2.content, scope);
+        }
+        Term::App(t1, t2) => {
+            share_nested(&mut t1.content, scope);
+            share_region(&mut t2.content, scope);
+        }
+        Term::Cond(t1, t2, t3) => {
+            share_nested(&mut t1.content, scope);
+            share_region(&mut t2.content, scope);
+            share_region(&mut t3.content, scope);
+        }
+        Term::Let(kind, name, t1, t2) => {
+            let binding = if let LetKind::Rec(_) = kind {
+                // A recursive function is pure if its body is pure assuming that the recursive
+                // calls are pure as well.
+                scope.push(Binding {
+                    name: name.content,
+                    pure_value: true,
+                    pure_call: true,
+                    inert: true,
+                });
+                let pure_call = is_pure_fn(&t1.content, scope);
+                scope.pop();
+                scope.push(Binding {
+                    name: name.content,
+                    pure_value: true,
+                    pure_call,
+                    inert: true,
+                });
+                share_region(&mut t1.content, scope);
+                scope.pop().unwrap()
+            } else {
+                let binding = Binding {
+                    name: name.content,
+                    // Strict bindings are evaluated before their bodies, so using their names
+                    // only reads a value.
+                    pure_value: matches!(kind, LetKind::Strict(_)) || is_pure(&t1.content, scope),
+                    pure_call: is_pure_fn(&t1.content, scope),
+                    inert: matches!(kind, LetKind::Strict(_)) || is_inert(&t1.content, scope),
+                };
+                share_region(&mut t1.content, scope);
+                binding
+            };
+            scope.push(binding);
+            share_region(&mut t2.content, scope);
+            scope.pop();
+        }
+    }
+}
+
+/// Returns the first pure subterm in the region of `term` that appears more than once in it and
+/// can be evaluated at the start of the region.
+///
+/// Larger subterms are found before the subterms inside them.
+fn repeated_subterm<'a>(term: &Term<'a>, scope: &[Binding<'a>]) -> Option<Located<Term<'a>>> {
+    let mut candidates = Vec::new();
+    collect_candidates(term, scope, &mut candidates);
+    candidates
+        .iter()
+        .enumerate()
+        .find(|(i, candidate)| {
+            candidates[i + 1..]
+                .iter()
+                .any(|other| candidate.content.structurally_eq(&other.content))
+                && inert_before(term, &candidate.content, scope) == Some(true)
+        })
+        .map(|(_, candidate)| (*candidate).clone())
+}
+
+/// Checks if every subterm evaluated in the region of `term` before the first occurrence of
+/// `shared` is inert.
+///
+/// Returns `None` if the search did not finish inside `term`.
+fn inert_before<'a>(term: &Term<'a>, shared: &Term<'a>, scope: &[Binding<'a>]) -> Option<bool> {
+    for child in region_children(term) {
+        if child.content.structurally_eq(shared) {
+            return Some(true);
+        }
+        match inert_before(&child.content, shared, scope) {
+            Some(inert) => return Some(inert),
+            // `shared` is not inside `child`, so `child` is evaluated completely before it.
+            None if !is_inert(&child.content, scope) => return Some(false),
+            None => (),
+        }
+    }
+    None
+}
+
+/// Collects the pure subterms in the region of `term` that are worth sharing, in pre-order.
+fn collect_candidates<'a, 't>(
+    term: &'t Term<'a>,
+    scope: &[Binding<'a>],
+    candidates: &mut Vec<&'t Located<Term<'a>>>,
+) {
+    for child in region_children(term) {
+        let compound = matches!(
+            child.content,
+            Term::App(..) | Term::UnaryOp(..) | Term::BinaryOp(..) | Term::Cond(..)
+        );
+        if compound && is_pure(&child.content, scope) {
+            candidates.push(child);
+        }
+        collect_candidates(&child.content, scope, candidates);
+    }
+}
+
+/// Replaces every subterm in the region of `term` that is structurally equal to `shared` by
+/// `name`. The locations of the replaced subterms are stored in `locs`.
+fn replace<'a>(term: &mut Term<'a>, shared: &Term<'a>, name: Name<'a>, locs: &mut Vec<Location>) {
+    for child in region_children_mut(term) {
+        if child.content.structurally_eq(shared) {
+            locs.push(child.loc);
+            child.content = Term::Var(name);
+        } else {
+            replace(&mut child.content, shared, name, locs);
+        }
+    }
+}
+
+/// Returns the subterms of `term` that belong to the same region, in evaluation order.
+fn region_children<'a, 't>(term: &'t Term<'a>) -> Vec<&'t Located<Term<'a>>> {
+    match term {
+        Term::UnaryOp(_, t1)
+        | Term::BinaryOp(BinOp::And, t1, _)
+        | Term::BinaryOp(BinOp::Or, t1, _)
+        | Term::App(t1, _)
+        | Term::Cond(t1, _, _) => vec![t1],
+        Term::BinaryOp(_, t1, t2) | Term::Seq(t1, t2) => vec![t1, t2],
+        Term::Var(_) | Term::Lit(_) | Term::PrimFn(_) | Term::Abs(..) | Term::Let(..) => vec![],
+    }
+}
+
+/// Mutable version of `region_children`.
+fn region_children_mut<'a, 't>(term: &'t mut Term<'a>) -> Vec<&'t mut Located<Term<'a>>> {
+    match term {
+        Term::UnaryOp(_, t1)
+        | Term::BinaryOp(BinOp::And, t1, _)
+        | Term::BinaryOp(BinOp::Or, t1, _)
+        | Term::App(t1, _)
+        | Term::Cond(t1, _, _) => vec![t1],
+        Term::BinaryOp(_, t1, t2) | Term::Seq(t1, t2) => vec![t1, t2],
+        Term::Var(_) | Term::Lit(_) | Term::PrimFn(_) | Term::Abs(..) | Term::Let(..) => vec![],
+    }
+}
+
+/// Checks if evaluating `term` has no effects.
+///
+/// Let bindings are never considered pure.
+fn is_pure<'a>(term: &Term<'a>, scope: &[Binding<'a>]) -> bool {
+    match term {
+        Term::Lit(_) | Term::PrimFn(_) | Term::Abs(..) => true,
+        Term::Var(name) => lookup(*name, scope).is_some_and(|binding| binding.pure_value),
+        Term::UnaryOp(_, t1) => is_pure(&t1.content, scope),
+        Term::BinaryOp(_, t1, t2) | Term::Seq(t1, t2) => {
+            is_pure(&t1.content, scope) && is_pure(&t2.content, scope)
+        }
+        Term::Cond(t1, t2, t3) => {
+            is_pure(&t1.content, scope)
+                && is_pure(&t2.content, scope)
+                && is_pure(&t3.content, scope)
+        }
+        Term::App(t1, t2) => {
+            let mut head = &t1.content;
+            let mut pure_args = is_pure(&t2.content, scope);
+            while let Term::App(t1, t2) = head {
+                head = &t1.content;
+                pure_args &= is_pure(&t2.content, scope);
+            }
+            let pure_head = match head {
+                Term::Var(name) => lookup(*name, scope).is_some_and(|binding| binding.pure_call),
+                Term::PrimFn(prim) => {
+                    !matches!(prim, Primitive::Print | Primitive::Dbg | Primitive::ReadInt)
+                }
+                _ => false,
+            };
+            pure_head && pure_args
+        }
+        Term::Let(..) => false,
+    }
+}
+
+/// Checks if evaluating `term` cannot fail, loop forever or have effects.
+fn is_inert<'a>(term: &Term<'a>, scope: &[Binding<'a>]) -> bool {
+    match term {
+        Term::Lit(_) | Term::PrimFn(_) | Term::Abs(..) => true,
+        Term::Var(name) => lookup(*name, scope).is_some_and(|binding| binding.inert),
+        _ => false,
+    }
+}
+
+/// Checks if `term` is a function whose body has no effects when its parameters are bound to
+/// pure terms.
+fn is_pure_fn<'a>(term: &Term<'a>, scope: &mut Vec<Binding<'a>>) -> bool {
+    match term {
+        Term::Abs(name, _, body) => {
+            scope.push(Binding {
+                name: *name,
+                pure_value: true,
+                pure_call: false,
+                inert: false,
+            });
+            let pure = match &body.content {
+                body @ Term::Abs(..) => is_pure_fn(body, scope),
+                body => is_pure(body, scope),
+            };
+            scope.pop();
+            pure
+        }
+        _ => false,
+    }
+}
+
+/// Returns the innermost binding of `name` in `scope`.
+fn lookup<'a, 's>(name: Name<'a>, scope: &'s [Binding<'a>]) -> Option<&'s Binding<'a>> {
+    scope.iter().rev().find(|binding| binding.name == name)
+}
@@ -0,0 +1,122 @@
+//! Inlining of small functions.
+//!
+//! This pass replaces the names of small non-recursive let-bound functions by the functions
+//! themselves. Then, every call to one of those functions is replaced by its body, with the
+//! argument bound to the name of the parameter. The bindings of the functions are kept, so the
+//! resulting term is equivalent to the original one.
+use std::collections::BTreeSet;
+
+use pijama_ast::{
+    location::Located,
+    node::{Literal, Name},
+};
+
+use crate::{LetKind, Term};
+
+impl<'a> Term<'a> {
+    /// Inlines every non-recursive let-bound function whose size is at most `max_size`.
+    ///
+    /// A name is not replaced inside a binder that would capture any free variable of the
+    /// function or that shadows the name of the function itself. Recursive functions are never
+    /// inlined.
+    pub fn inline(&mut self, max_size: usize) {
+        match self {
+            Term::Var(_) | Term::Lit(_) | Term::PrimFn(_) => (),
+            Term::Abs(_, _, body) => body.content.inline(max_size),
+            Term::UnaryOp(_, t1) => t1.content.inline(max_size),
+            Term::App(t1, t2) => {
+                t1.content.inline(max_size);
+                t2.content.inline(max_size);
+                let term = std::mem::replace(self, Term::Lit(Literal::Unit));
+                if let Term::App(func, arg) = term {
+                    *self = reduce_app(*func, arg);
+                }
+            }
+            Term::BinaryOp(_, t1, t2) | Term::Seq(t1, t2) => {
+                t1.content.inline(max_size);
+                t2.content.inline(max_size);
+            }
+            Term::Cond(t1, t2, t3) => {
+                t1.content.inline(max_size);
+                t2.content.inline(max_size);
+                t3.content.inline(max_size);
+            }
+            Term::Let(kind, name, t1, t2) => {
+                t1.content.inline(max_size);
+                if let (LetKind::NonRec(_), Term::Abs(..)) = (kind, &t1.content) {
+                    if t1.content.size() <= max_size {
+                        let free_vars = t1.content.free_vars();
+                        t2.content.substitute(name.content, &t1.content, &free_vars);
+                    }
+                }
+                t2.content.inline(max_size);
+            }
+        }
+    }
+
+    /// Replaces every free occurrence of `name` by `value`, whose free variables are `free_vars`.
+    fn substitute(&mut self, name: Name<'a>, value: &Term<'a>, free_vars: &BTreeSet<Name<'a>>) {
+        match self {
+            Term::Var(var) => {
+                if *var == name {
+                    *self = value.clone();
+                }
+            }
+            Term::Lit(_) | Term::PrimFn(_) => (),
+            Term::Abs(arg, _, body) => {
+                if can_bind(*arg, name, free_vars) {
+                    body.content.substitute(name, value, free_vars);
+                }
+            }
+            Term::UnaryOp(_, t1) => t1.content.substitute(name, value, free_vars),
+            Term::BinaryOp(_, t1, t2) | Term::App(t1, t2) | Term::Seq(t1, t2) => {
+                t1.content.substitute(name, value, free_vars);
+                t2.content.substitute(name, value, free_vars);
+            }
+            Term::Cond(t1, t2, t3) => {
+                t1.content.substitute(name, value, free_vars);
+                t2.content.substitute(name, value, free_vars);
+                t3.content.substitute(name, value, free_vars);
+            }
+            Term::Let(kind, binder, t1, t2) => {
+                let can_bind = can_bind(binder.content, name, free_vars);
+                // Recursive bindings are in scope inside their own definition.
+                if can_bind || !matches!(kind, LetKind::Rec(_)) {
+                    t1.content.substitute(name, value, free_vars);
+                }
+                if can_bind {
+                    t2.content.substitute(name, value, free_vars);
+                }
+            }
+        }
+    }
+}
+
+/// Reduces the application of a function `((λx:T. t1) t2)` to `(let x: T = t2 in t1)`.
+///
+/// If the function is the result of a non-recursive let binding, the binding is moved outside the
+/// application first, as long as the bound name is not free in the argument. This way, calls
+/// with several arguments are reduced completely. Any other application is left unchanged.
+fn reduce_app<'a>(func: Located<Term<'a>>, arg: Box<Located<Term<'a>>>) -> Term<'a> {
+    match func.content {
+        Term::Abs(name, ty, body) => Term::Let(
+            LetKind::NonRec(Some(func.loc.with_content(ty))),
+            func.loc.with_content(name),
+            arg,
+            body,
+        ),
+        Term::Let(kind @ LetKind::NonRec(_), name, t1, t2)
+            if !arg.content.free_vars().contains(&name.content) =>
+        {
+            let loc = t2.loc + arg.loc;
+            let t2 = reduce_app(*t2, arg);
+            Term::Let(kind, name, t1, Box::new(loc.with_content(t2)))
+        }
+        content => Term::App(Box::new(func.loc.with_content(content)), arg),
+    }
+}
+
+/// Checks if `name` can be replaced by a term with `free_vars` inside the scope of `binder`.
+fn can_bind<'a>(binder: Name<'a>, name: Name<'a>, free_vars: &BTreeSet<Name<'a>>) -> bool {
+    binder != name && !free_vars.contains(&binder)
+}
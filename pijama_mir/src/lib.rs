@@ -1,4 +1,7 @@
-use std::fmt::{Display, Formatter, Result};
+use std::{
+    collections::BTreeSet,
+    fmt::{Display, Formatter, Result},
+};
 
 use pijama_ast::{
     location::Located,
@@ -9,15 +12,35 @@ use pijama_ty::Ty;
 
 pub use lower::{LowerError, LowerResult};
 
+mod cse;
+mod inline;
 mod lower;
+mod simplify;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum LetKind {
     NonRec(Option<Located<Ty>>),
-    Rec(Located<Ty>),
+    Rec(Option<Located<Ty>>),
+    /// A non-recursive binding whose value is evaluated before the body of the binding, so it is
+    /// evaluated exactly once.
+    Strict(Option<Located<Ty>>),
+}
+
+impl LetKind {
+    /// Checks if two kinds of binding are the same, ignoring the locations of their types.
+    fn structurally_eq(&self, other: &LetKind) -> bool {
+        match (self, other) {
+            (LetKind::NonRec(ty1), LetKind::NonRec(ty2))
+            | (LetKind::Rec(ty1), LetKind::Rec(ty2))
+            | (LetKind::Strict(ty1), LetKind::Strict(ty2)) => {
+                ty1.as_ref().map(|ty| &ty.content) == ty2.as_ref().map(|ty| &ty.content)
+            }
+            _ => false,
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Term<'a> {
     Var(Name<'a>),
     Abs(Name<'a>, Ty, Box<Located<Term<'a>>>),
@@ -50,9 +73,22 @@ impl<'a> Display for Term<'a> {
             Term::App(t1, t2) => write!(f, "({} {})", t1, t2),
             Term::Lit(literal) => write!(f, "{}", literal),
             Term::Cond(t1, t2, t3) => write!(f, "(if {} then {} else {})", t1, t2, t3),
-            Term::Let(LetKind::Rec(ty), name, t1, t2) => {
+            Term::Let(LetKind::Rec(Some(ty)), name, t1, t2) => {
                 write!(f, "(let rec {} : {} = {} in {})", name, ty.content, t1, t2)
             }
+            Term::Let(LetKind::Rec(None), name, t1, t2) => {
+                write!(f, "(let rec {} = {} in {})", name, t1, t2)
+            }
+            Term::Let(LetKind::Strict(Some(ty)), name, t1, t2) => {
+                write!(
+                    f,
+                    "(let strict {} : {} = {} in {})",
+                    name, ty.content, t1, t2
+                )
+            }
+            Term::Let(LetKind::Strict(None), name, t1, t2) => {
+                write!(f, "(let strict {} = {} in {})", name, t1, t2)
+            }
             Term::Let(LetKind::NonRec(Some(ty)), name, t1, t2) => {
                 write!(f, "(let {} : {} = {} in {})", name, ty.content, t1, t2)
             }
@@ -67,6 +103,183 @@ impl<'a> Display for Term<'a> {
 
 impl<'a> Term<'a> {
     pub fn from_ast(blk: Block<'a>) -> LowerResult<Located<Self>> {
-        lower::lower_block(blk)
+        lower::lower_block(blk, false)
+    }
+
+    /// Lowers an AST where only the functions marked with `rec` are recursive.
+    ///
+    /// Any other function cannot reference itself inside its body.
+    pub fn from_ast_with_explicit_rec(blk: Block<'a>) -> LowerResult<Located<Self>> {
+        lower::lower_block(blk, true)
+    }
+
+    /// Returns the names that are referenced inside the term without being bound by it.
+    pub fn free_vars(&self) -> BTreeSet<Name<'a>> {
+        let mut free_vars = BTreeSet::new();
+        self.collect_free_vars(&mut Vec::new(), &mut free_vars);
+        free_vars
+    }
+
+    /// Adds the free variables of the term to `free_vars`. The names in `bound` are the ones
+    /// bound by the enclosing terms.
+    fn collect_free_vars(&self, bound: &mut Vec<Name<'a>>, free_vars: &mut BTreeSet<Name<'a>>) {
+        match self {
+            Term::Var(name) => {
+                if !bound.contains(name) {
+                    free_vars.insert(*name);
+                }
+            }
+            Term::Lit(_) | Term::PrimFn(_) => (),
+            Term::Abs(name, _, body) => {
+                bound.push(*name);
+                body.content.collect_free_vars(bound, free_vars);
+                bound.pop();
+            }
+            Term::UnaryOp(_, t1) => t1.content.collect_free_vars(bound, free_vars),
+            Term::BinaryOp(_, t1, t2) | Term::App(t1, t2) | Term::Seq(t1, t2) => {
+                t1.content.collect_free_vars(bound, free_vars);
+                t2.content.collect_free_vars(bound, free_vars);
+            }
+            Term::Cond(t1, t2, t3) => {
+                t1.content.collect_free_vars(bound, free_vars);
+                t2.content.collect_free_vars(bound, free_vars);
+                t3.content.collect_free_vars(bound, free_vars);
+            }
+            Term::Let(kind, name, t1, t2) => {
+                // The name of a recursive binding is also in scope inside its definition.
+                if let LetKind::Rec(_) = kind {
+                    bound.push(name.content);
+                    t1.content.collect_free_vars(bound, free_vars);
+                } else {
+                    t1.content.collect_free_vars(bound, free_vars);
+                    bound.push(name.content);
+                }
+                t2.content.collect_free_vars(bound, free_vars);
+                bound.pop();
+            }
+        }
+    }
+
+    /// Checks if two terms have the same structure, ignoring their locations.
+    ///
+    /// The locations of the type annotations in `let` bindings are ignored as well.
+    pub fn structurally_eq(&self, other: &Term<'_>) -> bool {
+        match (self, other) {
+            (Term::Var(name1), Term::Var(name2)) => name1 == name2,
+            (Term::Lit(lit1), Term::Lit(lit2)) => lit1 == lit2,
+            (Term::PrimFn(prim1), Term::PrimFn(prim2)) => prim1 == prim2,
+            (Term::Abs(name1, ty1, body1), Term::Abs(name2, ty2, body2)) => {
+                name1 == name2 && ty1 == ty2 && body1.content.structurally_eq(&body2.content)
+            }
+            (Term::UnaryOp(op1, t1), Term::UnaryOp(op2, t2)) => {
+                op1 == op2 && t1.content.structurally_eq(&t2.content)
+            }
+            (Term::BinaryOp(op1, t11, t12), Term::BinaryOp(op2, t21, t22)) => {
+                op1 == op2
+                    && t11.content.structurally_eq(&t21.content)
+                    && t12.content.structurally_eq(&t22.content)
+            }
+            (Term::App(t11, t12), Term::App(t21, t22))
+            | (Term::Seq(t11, t12), Term::Seq(t21, t22)) => {
+                t11.content.structurally_eq(&t21.content)
+                    && t12.content.structurally_eq(&t22.content)
+            }
+            (Term::Cond(t11, t12, t13), Term::Cond(t21, t22, t23)) => {
+                t11.content.structurally_eq(&t21.content)
+                    && t12.content.structurally_eq(&t22.content)
+                    && t13.content.structurally_eq(&t23.content)
+            }
+            (Term::Let(kind1, name1, t11, t12), Term::Let(kind2, name2, t21, t22)) => {
+                kind1.structurally_eq(kind2)
+                    && name1.content == name2.content
+                    && t11.content.structurally_eq(&t21.content)
+                    && t12.content.structurally_eq(&t22.content)
+            }
+            _ => false,
+        }
+    }
+
+    /// Checks if two terms are equal up to the renaming of their bound variables, ignoring their
+    /// locations.
+    ///
+    /// For example, `fn(x: Int) do x end` and `fn(y: Int) do y end` are alpha-equivalent.
+    pub fn alpha_eq(&self, other: &Term<'_>) -> bool {
+        self.alpha_eq_in(other, &mut Vec::new())
+    }
+
+    /// Checks if two terms are alpha-equivalent. `bound` has the pairs of names bound by the
+    /// enclosing terms, with the innermost bindings at the end.
+    fn alpha_eq_in<'b>(&self, other: &Term<'b>, bound: &mut Vec<(&'a str, &'b str)>) -> bool {
+        match (self, other) {
+            (Term::Var(name1), Term::Var(name2)) => {
+                let binding = bound
+                    .iter()
+                    .rev()
+                    .find(|(bound1, bound2)| *bound1 == name1.0 || *bound2 == name2.0);
+                match binding {
+                    Some((bound1, bound2)) => *bound1 == name1.0 && *bound2 == name2.0,
+                    // Free variables must have the same name.
+                    None => name1.0 == name2.0,
+                }
+            }
+            (Term::Lit(lit1), Term::Lit(lit2)) => lit1 == lit2,
+            (Term::PrimFn(prim1), Term::PrimFn(prim2)) => prim1 == prim2,
+            (Term::Abs(name1, ty1, body1), Term::Abs(name2, ty2, body2)) => {
+                bound.push((name1.0, name2.0));
+                let eq = ty1 == ty2 && body1.content.alpha_eq_in(&body2.content, bound);
+                bound.pop();
+                eq
+            }
+            (Term::UnaryOp(op1, t1), Term::UnaryOp(op2, t2)) => {
+                op1 == op2 && t1.content.alpha_eq_in(&t2.content, bound)
+            }
+            (Term::BinaryOp(op1, t11, t12), Term::BinaryOp(op2, t21, t22)) => {
+                op1 == op2
+                    && t11.content.alpha_eq_in(&t21.content, bound)
+                    && t12.content.alpha_eq_in(&t22.content, bound)
+            }
+            (Term::App(t11, t12), Term::App(t21, t22))
+            | (Term::Seq(t11, t12), Term::Seq(t21, t22)) => {
+                t11.content.alpha_eq_in(&t21.content, bound)
+                    && t12.content.alpha_eq_in(&t22.content, bound)
+            }
+            (Term::Cond(t11, t12, t13), Term::Cond(t21, t22, t23)) => {
+                t11.content.alpha_eq_in(&t21.content, bound)
+                    && t12.content.alpha_eq_in(&t22.content, bound)
+                    && t13.content.alpha_eq_in(&t23.content, bound)
+            }
+            (Term::Let(kind1, name1, t11, t12), Term::Let(kind2, name2, t21, t22)) => {
+                if !kind1.structurally_eq(kind2) {
+                    return false;
+                }
+                // The name of a recursive binding is also in scope inside its definition.
+                let eq = if let LetKind::Rec(_) = kind1 {
+                    bound.push((name1.content.0, name2.content.0));
+                    t11.content.alpha_eq_in(&t21.content, bound)
+                } else {
+                    let eq = t11.content.alpha_eq_in(&t21.content, bound);
+                    bound.push((name1.content.0, name2.content.0));
+                    eq
+                };
+                let eq = eq && t12.content.alpha_eq_in(&t22.content, bound);
+                bound.pop();
+                eq
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the size of the term, counted as the number of nodes in it.
+    pub fn size(&self) -> usize {
+        match self {
+            Term::Var(_) | Term::Lit(_) | Term::PrimFn(_) => 1,
+            Term::Abs(_, _, body) => 1 + body.content.size(),
+            Term::UnaryOp(_, t1) => 1 + t1.content.size(),
+            Term::BinaryOp(_, t1, t2)
+            | Term::App(t1, t2)
+            | Term::Let(_, _, t1, t2)
+            | Term::Seq(t1, t2) => 1 + t1.content.size() + t2.content.size(),
+            Term::Cond(t1, t2, t3) => 1 + t1.content.size() + t2.content.size() + t3.content.size(),
+        }
     }
 }
@@ -1,24 +1,30 @@
 use thiserror::Error;
 
-use std::mem::discriminant;
+use std::{collections::VecDeque, mem::discriminant};
 
 use pijama_ast::{
     analysis::is_fn_def_recursive,
     location::{Located, Location},
-    node::{BinOp, Block, Branch, Expression, Name, Node, Statement, UnOp},
-    ty::TyAnnotation,
+    node::{BinOp, Block, Branch, Expression, Literal, Name, Node, Param, Statement, UnOp},
+    ty::{Ty as AstTy, TyAnnotation},
 };
 
 use pijama_ty::Ty;
 
 use crate::{LetKind, Term};
 
+/// Names used by `lower_cond_with_bindings`, `lower_chain` and `lower_fn_def`. These are not valid identifiers,
+/// so they cannot shadow or be shadowed by any name in the program.
+const ELSE: &str = "_else";
+const UNIT: &str = "_unit";
+const OPERANDS: [&str; 2] = ["_cmp0", "_cmp1"];
+/// Name bound to the default values of parameters by `lower_fn_def`.
+const DEFAULT: &str = "_default";
+
 pub type LowerResult<T> = Result<T, LowerError>;
 
 #[derive(Error, Debug)]
 pub enum LowerError {
-    #[error("Required type annotation is missing")]
-    RequiredTy(Location),
     #[error("Anonymous functions cannot have a return type annotation")]
     AnonWithTy(Location),
 }
@@ -26,7 +32,7 @@ pub enum LowerError {
 impl LowerError {
     pub fn loc(&self) -> Location {
         match self {
-            LowerError::RequiredTy(loc) | LowerError::AnonWithTy(loc) => *loc,
+            LowerError::AnonWithTy(loc) => *loc,
         }
     }
 }
@@ -39,193 +45,480 @@ impl PartialEq for LowerError {
 
 impl Eq for LowerError {}
 
-pub fn lower_block<'a>(mut block: Block<'a>) -> LowerResult<Located<Term<'a>>> {
-    if let Some(node) = block.nodes.pop_front() {
-        match node {
-            Node::Expr(expr) => {
-                let head = lower_expression(expr)?;
-                let tail = lower_block(block)?;
-                let loc = head.loc + tail.loc;
-                Ok(loc.with_content(Term::Seq(Box::new(head), Box::new(tail))))
-            }
-            Node::Stat(stat) => match stat.content {
-                Statement::Assign(lhs, rhs) => lower_assign(stat.loc, lhs, rhs, block),
-                Statement::FnDef(name, args, body) => {
-                    lower_fn_def(stat.loc, name, args, body, block)
-                }
-            },
-        }
-    } else {
-        lower_expression(*block.expr)
+pub fn lower_block(block: Block<'_>, explicit_rec: bool) -> LowerResult<Located<Term<'_>>> {
+    Lowerer {
+        scope: Vec::new(),
+        explicit_rec,
+        next_var: 0,
     }
+    .lower_block(block)
 }
 
-fn lower_expression(expr: Located<Expression<'_>>) -> LowerResult<Located<Term<'_>>> {
-    let loc = expr.loc;
-    match expr.content {
-        Expression::Name(name) => Ok(loc.with_content(Term::Var(name))),
-        Expression::Literal(lit) => Ok(loc.with_content(Term::Lit(lit))),
-        Expression::PrimFn(prim) => Ok(loc.with_content(Term::PrimFn(prim))),
-        Expression::Cond(if_branch, branches, el_blk) => {
-            lower_cond(loc, if_branch, branches, el_blk)
-        }
-        Expression::Call(func, args) => lower_call(loc, *func, args),
-        Expression::BinaryOp(bin_op, expr1, expr2) => lower_binary_op(loc, bin_op, *expr1, *expr2),
-        Expression::UnaryOp(un_op, expr) => lower_unary_op(loc, un_op, *expr),
-        Expression::AnonFn(args, body) => lower_anon_fn(loc, args, body),
-    }
+/// A name in scope during lowering together with the default values of its parameters.
+///
+/// Only names bound by function definitions can have default values. Any other binding has an
+/// empty list of defaults and is only stored to shadow the previous bindings with the same name.
+struct Scoped<'a> {
+    name: Name<'a>,
+    defaults: Vec<Option<Located<Literal>>>,
 }
 
-fn lower_cond<'a>(
-    loc: Location,
-    if_branch: Branch<'a>,
-    branches: Vec<Branch<'a>>,
-    el_blk: Block<'a>,
-) -> LowerResult<Located<Term<'a>>> {
-    let mut el_term = Box::new(lower_block(el_blk)?);
-
-    for branch in branches.into_iter().rev() {
-        el_term = Box::new(loc.with_content(Term::Cond(
-            Box::new(lower_block(branch.cond)?),
-            Box::new(lower_block(branch.body)?),
-            el_term,
-        )));
-    }
-
-    let if_blk = if_branch.cond;
-    let do_blk = if_branch.body;
-
-    Ok(loc.with_content(Term::Cond(
-        Box::new(lower_block(if_blk)?),
-        Box::new(lower_block(do_blk)?),
-        el_term,
-    )))
+/// Lowers the AST into the MIR while keeping track of the names in scope.
+struct Lowerer<'a> {
+    /// Stack with the bindings done in the current scope.
+    scope: Vec<Scoped<'a>>,
+    /// Only functions marked with `rec` are recursive. Otherwise, recursion is detected
+    /// automatically.
+    explicit_rec: bool,
+    /// Index of the next type variable used for a missing type annotation.
+    next_var: usize,
 }
 
-fn lower_call<'a>(
-    loc: Location,
-    func: Located<Expression<'a>>,
-    args: Vec<Located<Expression<'a>>>,
-) -> LowerResult<Located<Term<'a>>> {
-    let mut term = lower_expression(func)?;
-    for arg in args {
-        term = loc.with_content(Term::App(Box::new(term), Box::new(lower_expression(arg)?)));
+impl<'a> Lowerer<'a> {
+    /// Pushes a binding without default values into the scope.
+    fn push_name(&mut self, name: Name<'a>) {
+        self.scope.push(Scoped {
+            name,
+            defaults: Vec::new(),
+        });
     }
-    Ok(term)
-}
 
-fn lower_binary_op<'a>(
-    loc: Location,
-    bin_op: BinOp,
-    expr1: Located<Expression<'a>>,
-    expr2: Located<Expression<'a>>,
-) -> LowerResult<Located<Term<'a>>> {
-    Ok(loc.with_content(Term::BinaryOp(
-        bin_op,
-        Box::new(lower_expression(expr1)?),
-        Box::new(lower_expression(expr2)?),
-    )))
-}
+    /// Lowers the type annotation of a parameter. If the annotation is missing, a new type
+    /// variable is returned instead so the type-checker can infer the type.
+    fn param_ty(&mut self, ty: AstTy) -> Ty {
+        Ty::from_ast(ty).unwrap_or_else(|| {
+            let var = Ty::Var(self.next_var);
+            self.next_var += 1;
+            var
+        })
+    }
 
-fn lower_unary_op(
-    loc: Location,
-    un_op: UnOp,
-    expr: Located<Expression<'_>>,
-) -> LowerResult<Located<Term<'_>>> {
-    Ok(loc.with_content(Term::UnaryOp(un_op, Box::new(lower_expression(expr)?))))
-}
+    /// Returns the default values of the parameters of the function bound to `name`.
+    fn defaults(&self, name: Name<'a>) -> &[Option<Located<Literal>>] {
+        self.scope
+            .iter()
+            .rev()
+            .find(|scoped| scoped.name == name)
+            .map(|scoped| scoped.defaults.as_slice())
+            .unwrap_or_default()
+    }
 
-fn lower_assign<'a>(
-    loc: Location,
-    lhs: TyAnnotation<Located<Name<'a>>>,
-    rhs: Located<Expression<'a>>,
-    tail: Block<'a>,
-) -> LowerResult<Located<Term<'a>>> {
-    let rhs = lower_expression(rhs)?;
-
-    let opt_ty = if let Some(ty) = Ty::from_ast(lhs.ty.content) {
-        Some(lhs.ty.loc.with_content(ty))
-    } else {
-        None
-    };
-
-    let tail = lower_block(tail)?;
-
-    Ok(loc.with_content(Term::Let(
-        LetKind::NonRec(opt_ty),
-        lhs.item,
-        Box::new(rhs),
-        Box::new(tail),
-    )))
-}
+    fn lower_block(&mut self, mut block: Block<'a>) -> LowerResult<Located<Term<'a>>> {
+        if let Some(node) = block.nodes.pop_front() {
+            match node {
+                Node::Expr(expr) => {
+                    let head = self.lower_expression(expr)?;
+                    let tail = self.lower_block(block)?;
+                    let loc = head.loc + tail.loc;
+                    Ok(loc.with_content(Term::Seq(Box::new(head), Box::new(tail))))
+                }
+                Node::Stat(stat) => match stat.content {
+                    Statement::Assign(lhs, rhs) => self.lower_assign(stat.loc, lhs, rhs, block),
+                    Statement::FnDef(name, args, body, rec) => {
+                        self.lower_fn_def(stat.loc, name, args, body, rec, block)
+                    }
+                },
+            }
+        } else {
+            self.lower_expression(*block.expr)
+        }
+    }
 
-fn lower_fn_def<'a>(
-    loc: Location,
-    name: Located<Name<'a>>,
-    args: Vec<TyAnnotation<Located<Name<'a>>>>,
-    body: TyAnnotation<Block<'a>>,
-    tail: Block<'a>,
-) -> LowerResult<Located<Term<'a>>> {
-    // if the user added a return type annotation, we transform this type into the type of the
-    // function using the arguments' annotations.
-    let ty_loc = body.ty.loc;
-    let opt_ty = if let Some(mut ty) = Ty::from_ast(body.ty.content) {
-        for arg in args.iter().rev() {
-            let arg_ty = Ty::from_ast(arg.ty.content.clone())
-                .ok_or_else(|| LowerError::RequiredTy(arg.ty.loc))?;
-            ty = Ty::Arrow(Box::new(arg_ty), Box::new(ty));
+    fn lower_expression(
+        &mut self,
+        expr: Located<Expression<'a>>,
+    ) -> LowerResult<Located<Term<'a>>> {
+        let loc = expr.loc;
+        match expr.content {
+            Expression::Name(name) => Ok(loc.with_content(Term::Var(name))),
+            Expression::Literal(lit) => Ok(loc.with_content(Term::Lit(lit))),
+            Expression::PrimFn(prim) => Ok(loc.with_content(Term::PrimFn(prim))),
+            Expression::Cond(if_branch, branches, el_blk) => {
+                self.lower_cond(loc, if_branch, branches, el_blk)
+            }
+            Expression::Call(func, args) => self.lower_call(loc, *func, args),
+            Expression::BinaryOp(bin_op, expr1, expr2) => {
+                self.lower_binary_op(loc, bin_op, *expr1, *expr2)
+            }
+            Expression::Chain(first, rest) => self.lower_chain(loc, *first, rest),
+            Expression::UnaryOp(un_op, expr) => self.lower_unary_op(loc, un_op, *expr),
+            Expression::AnonFn(args, body) => self.lower_anon_fn(loc, args, body),
+            Expression::Block(block) => self
+                .lower_block(block)
+                .map(|term| loc.with_content(term.content)),
         }
-        Some(ty_loc.with_content(ty))
-    } else {
-        None
-    };
-
-    // we need to decide if the function is recursive or not
-    let kind = if is_fn_def_recursive(name.content, &body.item) {
-        // if the function is recursive, we need the return type.
-        opt_ty
-            .map(LetKind::Rec)
-            .ok_or_else(|| LowerError::RequiredTy(name.loc))?
-    } else {
-        LetKind::NonRec(opt_ty)
-    };
-
-    let mut term = lower_block(body.item)?;
-
-    for arg in args.into_iter().rev() {
-        let loc = arg.ty.loc;
-        term = loc.with_content(Term::Abs(
-            arg.item.content,
-            Ty::from_ast(arg.ty.content).ok_or_else(|| LowerError::RequiredTy(loc))?,
-            Box::new(term),
-        ));
     }
 
-    let tail = lower_block(tail)?;
+    fn lower_cond(
+        &mut self,
+        loc: Location,
+        if_branch: Branch<'a>,
+        mut branches: Vec<Branch<'a>>,
+        el_blk: Block<'a>,
+    ) -> LowerResult<Located<Term<'a>>> {
+        // The `elif` branches are lowered as a conditional inside the `else` block.
+        let el_blk = if branches.is_empty() {
+            el_blk
+        } else {
+            let elif_branch = branches.remove(0);
+            Block {
+                nodes: VecDeque::new(),
+                expr: Box::new(loc.with_content(Expression::Cond(elif_branch, branches, el_blk))),
+            }
+        };
 
-    term = loc.with_content(Term::Let(kind, name, Box::new(term), Box::new(tail)));
+        if !if_branch.cond.nodes.is_empty() {
+            return self.lower_cond_with_bindings(loc, if_branch, el_blk);
+        }
 
-    Ok(term)
-}
+        let el_term = self.lower_block(el_blk)?;
+        let if_blk = if_branch.cond;
+        let do_blk = if_branch.body;
 
-fn lower_anon_fn<'a>(
-    loc: Location,
-    args: Vec<TyAnnotation<Located<Name<'a>>>>,
-    body: TyAnnotation<Block<'a>>,
-) -> LowerResult<Located<Term<'a>>> {
-    if Ty::from_ast(body.ty.content).is_some() {
-        return Err(LowerError::AnonWithTy(body.ty.loc));
+        Ok(loc.with_content(Term::Cond(
+            Box::new(self.lower_block(if_blk)?),
+            Box::new(self.lower_block(do_blk)?),
+            Box::new(el_term),
+        )))
     }
 
-    let mut term = lower_block(body.item)?;
+    /// Lowers a conditional whose condition has statements before its final expression.
+    ///
+    /// The bindings done in the condition are visible in the body of the branch but not in the
+    /// `else` block. To achieve this, the `else` block is bound to a function that is called
+    /// inside the bindings of the condition:
+    ///
+    /// ```text
+    /// _else = fn(_unit: Unit) do <else> end
+    /// <nodes of the condition>
+    /// if <expression of the condition> do <body> else _else(unit) end
+    /// ```
+    fn lower_cond_with_bindings(
+        &mut self,
+        loc: Location,
+        if_branch: Branch<'a>,
+        el_blk: Block<'a>,
+    ) -> LowerResult<Located<Term<'a>>> {
+        let annotated = |name, ty| TyAnnotation {
+            item: loc.with_content(Name(name)),
+            ty: loc.with_content(ty),
+        };
+
+        let thunk = Expression::AnonFn(
+            vec![annotated(UNIT, AstTy::Unit)],
+            TyAnnotation {
+                item: el_blk,
+                ty: loc.with_content(AstTy::Missing),
+            },
+        );
+
+        let force = Expression::Call(
+            Box::new(loc.with_content(Expression::Name(Name(ELSE)))),
+            vec![loc.with_content(Expression::Literal(Literal::Unit))],
+        );
+
+        let Branch { cond, body } = if_branch;
+        let cond = Block {
+            nodes: cond.nodes,
+            expr: Box::new(loc.with_content(Expression::Cond(
+                Branch {
+                    cond: Block {
+                        nodes: VecDeque::new(),
+                        expr: cond.expr,
+                    },
+                    body,
+                },
+                Vec::new(),
+                Block {
+                    nodes: VecDeque::new(),
+                    expr: Box::new(loc.with_content(force)),
+                },
+            ))),
+        };
+
+        let mut nodes = VecDeque::new();
+        nodes.push_back(Node::Stat(loc.with_content(Statement::Assign(
+            annotated(ELSE, AstTy::Missing),
+            loc.with_content(thunk),
+        ))));
+
+        self.lower_block(Block {
+            nodes,
+            expr: Box::new(loc.with_content(Expression::Block(cond))),
+        })
+    }
+
+    fn lower_call(
+        &mut self,
+        loc: Location,
+        func: Located<Expression<'a>>,
+        args: Vec<Located<Expression<'a>>>,
+    ) -> LowerResult<Located<Term<'a>>> {
+        // If the function is called by its name, the missing arguments are replaced by the default
+        // values of the corresponding parameters.
+        let defaults = match func.content {
+            Expression::Name(name) => self
+                .defaults(name)
+                .iter()
+                .skip(args.len())
+                .map_while(|default| *default)
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        let mut term = self.lower_expression(func)?;
+        let mut args = args
+            .into_iter()
+            .map(|arg| self.lower_expression(arg))
+            .collect::<LowerResult<Vec<_>>>()?;
+        args.extend(defaults.into_iter().map(|lit| lit.map(Term::Lit)));
+
+        let len = args.len();
+        for (i, arg) in args.into_iter().enumerate() {
+            // The last application spans the whole call. Each one of the others spans from the
+            // start of the call to the end of its argument. The start of the call is not the start
+            // of the function when a user-defined operator is used.
+            let app_loc = if i + 1 == len { loc } else { loc + arg.loc };
+            term = app_loc.with_content(Term::App(Box::new(term), Box::new(arg)));
+        }
+        Ok(term)
+    }
+
+    fn lower_binary_op(
+        &mut self,
+        loc: Location,
+        bin_op: BinOp,
+        expr1: Located<Expression<'a>>,
+        expr2: Located<Expression<'a>>,
+    ) -> LowerResult<Located<Term<'a>>> {
+        Ok(loc.with_content(Term::BinaryOp(
+            bin_op,
+            Box::new(self.lower_expression(expr1)?),
+            Box::new(self.lower_expression(expr2)?),
+        )))
+    }
 
-    for arg in args.into_iter().rev() {
-        term = loc.with_content(Term::Abs(
-            arg.item.content,
-            Ty::from_ast(arg.ty.content).unwrap(),
-            Box::new(term),
+    /// Lowers a chain of comparisons into a conjunction of comparisons.
+    ///
+    /// Every operand but the last one is bound using a strict `let` so each operand is evaluated
+    /// exactly once and in order. For example, `a < b < c` is lowered to
+    ///
+    /// ```text
+    /// let strict _cmp0 = a in let strict _cmp1 = b in (_cmp0 < _cmp1) && (_cmp1 < c)
+    /// ```
+    ///
+    /// Only two names are needed because each comparison uses the names of its operands before
+    /// the next binding shadows the older one.
+    fn lower_chain(
+        &mut self,
+        loc: Location,
+        first: Located<Expression<'a>>,
+        rest: Vec<(BinOp, Located<Expression<'a>>)>,
+    ) -> LowerResult<Located<Term<'a>>> {
+        let mut ops = Vec::with_capacity(rest.len());
+        let mut operands = vec![self.lower_expression(first)?];
+        for (op, expr) in rest {
+            ops.push(op);
+            operands.push(self.lower_expression(expr)?);
+        }
+
+        let var = |i: usize, loc: Location| loc.with_content(Term::Var(Name(OPERANDS[i % 2])));
+        let bind = |i: usize, value: Located<Term<'a>>, body: Located<Term<'a>>| {
+            let loc = value.loc + body.loc;
+            loc.with_content(Term::Let(
+                LetKind::Strict(None),
+                value.loc.with_content(Name(OPERANDS[i % 2])),
+                Box::new(value),
+                Box::new(body),
+            ))
+        };
+
+        // The last comparison uses the last operand directly.
+        let last = operands.pop().unwrap();
+        let mut i = ops.len() - 1;
+        let mut term = (operands[i].loc + last.loc).with_content(Term::BinaryOp(
+            ops[i],
+            Box::new(var(i, operands[i].loc)),
+            Box::new(last),
         ));
+
+        while let Some(operand) = operands.pop() {
+            if i > 0 {
+                i -= 1;
+                let cmp = (operands[i].loc + operand.loc).with_content(Term::BinaryOp(
+                    ops[i],
+                    Box::new(var(i, operands[i].loc)),
+                    Box::new(var(i + 1, operand.loc)),
+                ));
+                let and = (cmp.loc + term.loc).with_content(Term::BinaryOp(
+                    BinOp::And,
+                    Box::new(cmp),
+                    Box::new(term),
+                ));
+                term = bind(i + 1, operand, and);
+            } else {
+                term = bind(0, operand, term);
+            }
+        }
+
+        Ok(loc.with_content(term.content))
+    }
+
+    fn lower_unary_op(
+        &mut self,
+        loc: Location,
+        un_op: UnOp,
+        expr: Located<Expression<'a>>,
+    ) -> LowerResult<Located<Term<'a>>> {
+        Ok(loc.with_content(Term::UnaryOp(un_op, Box::new(self.lower_expression(expr)?))))
     }
 
-    Ok(term)
+    fn lower_assign(
+        &mut self,
+        loc: Location,
+        lhs: TyAnnotation<Located<Name<'a>>>,
+        rhs: Located<Expression<'a>>,
+        tail: Block<'a>,
+    ) -> LowerResult<Located<Term<'a>>> {
+        let rhs = self.lower_expression(rhs)?;
+
+        let opt_ty = if let Some(ty) = Ty::from_ast(lhs.ty.content) {
+            Some(lhs.ty.loc.with_content(ty))
+        } else {
+            None
+        };
+
+        self.push_name(lhs.item.content);
+        let tail = self.lower_block(tail)?;
+        self.scope.pop();
+
+        Ok(loc.with_content(Term::Let(
+            LetKind::NonRec(opt_ty),
+            lhs.item,
+            Box::new(rhs),
+            Box::new(tail),
+        )))
+    }
+
+    fn lower_fn_def(
+        &mut self,
+        loc: Location,
+        name: Located<Name<'a>>,
+        params: Vec<Param<'a>>,
+        body: TyAnnotation<Block<'a>>,
+        rec: bool,
+        tail: Block<'a>,
+    ) -> LowerResult<Located<Term<'a>>> {
+        // if the user added a return type annotation, we transform this type into the type of the
+        // function using the arguments' annotations.
+        let ty_loc = body.ty.loc;
+        let (args, defaults): (Vec<_>, Vec<_>) = params
+            .into_iter()
+            .map(|param| (param.ann, param.default))
+            .unzip();
+
+        let arg_tys = args
+            .iter()
+            .map(|arg| self.param_ty(arg.ty.content.clone()))
+            .collect::<Vec<_>>();
+
+        let opt_ty = if let Some(mut ty) = Ty::from_ast(body.ty.content) {
+            for arg_ty in arg_tys.iter().rev() {
+                ty = Ty::Arrow(Box::new(arg_ty.clone()), Box::new(ty));
+            }
+            Some(ty_loc.with_content(ty))
+        } else {
+            None
+        };
+
+        // we need to decide if the function is recursive or not, unless the user did it already.
+        let rec = rec || (!self.explicit_rec && is_fn_def_recursive(name.content, &body.item));
+        let kind = if rec {
+            LetKind::Rec(opt_ty)
+        } else {
+            LetKind::NonRec(opt_ty)
+        };
+
+        // Each default value is bound to a name annotated with the type of its parameter, so the
+        // type-checker checks it even if no call uses it.
+        let default_checks = defaults
+            .iter()
+            .zip(&args)
+            .zip(&arg_tys)
+            .filter_map(|((default, arg), arg_ty)| {
+                let default = (*default)?;
+                let ty = arg.ty.loc.with_content(arg_ty.clone());
+                Some((default, ty))
+            })
+            .collect::<Vec<_>>();
+
+        let scoped = Scoped {
+            name: name.content,
+            defaults,
+        };
+
+        // The arguments are in scope inside the body of the function. The function itself is only
+        // in scope inside its body if it is recursive.
+        let scoped = if rec {
+            self.scope.push(scoped);
+            None
+        } else {
+            Some(scoped)
+        };
+        for arg in &args {
+            self.push_name(arg.item.content);
+        }
+        let mut term = self.lower_block(body.item)?;
+        self.scope.truncate(self.scope.len() - args.len());
+        if let Some(scoped) = scoped {
+            self.scope.push(scoped);
+        }
+
+        for (arg, arg_ty) in args.into_iter().zip(arg_tys).rev() {
+            term = arg
+                .ty
+                .loc
+                .with_content(Term::Abs(arg.item.content, arg_ty, Box::new(term)));
+        }
+
+        let tail = self.lower_block(tail)?;
+        self.scope.pop();
+
+        term = loc.with_content(Term::Let(kind, name, Box::new(term), Box::new(tail)));
+
+        for (default, ty) in default_checks.into_iter().rev() {
+            term = loc.with_content(Term::Let(
+                LetKind::NonRec(Some(ty)),
+                default.loc.with_content(Name(DEFAULT)),
+                Box::new(default.map(Term::Lit)),
+                Box::new(term),
+            ));
+        }
+
+        Ok(term)
+    }
+
+    fn lower_anon_fn(
+        &mut self,
+        loc: Location,
+        args: Vec<TyAnnotation<Located<Name<'a>>>>,
+        body: TyAnnotation<Block<'a>>,
+    ) -> LowerResult<Located<Term<'a>>> {
+        if Ty::from_ast(body.ty.content).is_some() {
+            return Err(LowerError::AnonWithTy(body.ty.loc));
+        }
+
+        for arg in &args {
+            self.push_name(arg.item.content);
+        }
+        let mut term = self.lower_block(body.item)?;
+        self.scope.truncate(self.scope.len() - args.len());
+
+        for arg in args.into_iter().rev() {
+            let arg_ty = self.param_ty(arg.ty.content);
+            term = loc.with_content(Term::Abs(arg.item.content, arg_ty, Box::new(term)));
+        }
+
+        Ok(term)
+    }
 }
@@ -0,0 +1,39 @@
+//! Simplification of conditionals with a constant condition.
+//!
+//! This pass replaces every conditional whose condition is a boolean literal by the branch that
+//! would be taken. The other branch is discarded, so its effects never happen, just like during
+//! evaluation.
+use pijama_ast::node::Literal;
+
+use crate::Term;
+
+impl<'a> Term<'a> {
+    /// Replaces every conditional with a literal condition by the branch it takes.
+    pub fn simplify(&mut self) {
+        match self {
+            Term::Var(_) | Term::Lit(_) | Term::PrimFn(_) => (),
+            Term::Abs(_, _, body) => body.content.simplify(),
+            Term::UnaryOp(_, t1) => t1.content.simplify(),
+            Term::BinaryOp(_, t1, t2)
+            | Term::App(t1, t2)
+            | Term::Let(_, _, t1, t2)
+            | Term::Seq(t1, t2) => {
+                t1.content.simplify();
+                t2.content.simplify();
+            }
+            Term::Cond(t1, t2, t3) => {
+                t1.content.simplify();
+                if let Term::Lit(Literal::Bool(cond)) = t1.content {
+                    let branch = if cond { t2 } else { t3 };
+                    let mut branch =
+                        std::mem::replace(&mut branch.content, Term::Lit(Literal::Unit));
+                    branch.simplify();
+                    *self = branch;
+                } else {
+                    t2.content.simplify();
+                    t3.content.simplify();
+                }
+            }
+        }
+    }
+}
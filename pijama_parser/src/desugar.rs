@@ -0,0 +1,91 @@
+//! Syntactic sugar that is removed during parsing.
+use pijama_ast::{
+    location::{Located, Location},
+    node::{BinOp, Block, Expression, Name},
+    ty::{Ty, TyAnnotation},
+};
+
+/// Names of the parameters of the functions built by `bin_op_fn`. These are not valid
+/// identifiers, so they cannot shadow or be shadowed by any name in the program.
+const LHS: &str = "_lhs";
+const RHS: &str = "_rhs";
+/// Name of the parameter of the functions built by `section`.
+const HOLE: &str = "_hole";
+
+/// Returns an anonymous function that applies `op` to its two parameters.
+///
+/// The types of the parameters are the ones expected by `op`. `&` and `|` take either `Int`s or
+/// `Bool`s, so their parameters do not have a type annotation and their type is inferred from
+/// the uses of the function. `==` and `!=` can only compare integers when used as functions
+/// because parameters cannot have polymorphic types.
+///
+/// Every node of the returned function is located at `loc`.
+pub(crate) fn bin_op_fn<'a>(op: BinOp, loc: Location) -> Expression<'a> {
+    let ty = match op {
+        BinOp::And | BinOp::Or => Ty::Bool,
+        BinOp::BitAnd | BinOp::BitOr => Ty::Missing,
+        _ => Ty::Int,
+    };
+
+    let param = |name| TyAnnotation {
+        item: loc.with_content(Name(name)),
+        ty: loc.with_content(ty.clone()),
+    };
+
+    let operand = |name| Box::new(loc.with_content(Expression::Name(Name(name))));
+
+    let body = Block {
+        nodes: Default::default(),
+        expr: Box::new(loc.with_content(Expression::BinaryOp(op, operand(LHS), operand(RHS)))),
+    };
+
+    Expression::AnonFn(
+        vec![param(LHS), param(RHS)],
+        TyAnnotation {
+            item: body,
+            ty: loc.with_content(Ty::Missing),
+        },
+    )
+}
+
+/// Returns an anonymous function taking the place of the hole `_` in an operator section.
+///
+/// The section is `_ op operand` if `hole_first` is true and `operand op _` otherwise. The type of
+/// the parameter is inferred from its usage.
+pub(crate) fn section<'a>(
+    op: BinOp,
+    hole: Location,
+    operand: Located<Expression<'a>>,
+    hole_first: bool,
+) -> Expression<'a> {
+    let param = TyAnnotation {
+        item: hole.with_content(Name(HOLE)),
+        ty: hole.with_content(Ty::Missing),
+    };
+
+    let hole_expr = Box::new(hole.with_content(Expression::Name(Name(HOLE))));
+    let (loc, expr) = if hole_first {
+        (
+            hole + operand.loc,
+            Expression::BinaryOp(op, hole_expr, Box::new(operand)),
+        )
+    } else {
+        (
+            operand.loc + hole,
+            Expression::BinaryOp(op, Box::new(operand), hole_expr),
+        )
+    };
+
+    let body = Block {
+        nodes: Default::default(),
+        expr: Box::new(loc.with_content(expr)),
+    };
+
+    Expression::AnonFn(
+        vec![param],
+        TyAnnotation {
+            item: body,
+            ty: loc.with_content(Ty::Missing),
+        },
+    )
+}
@@ -1,6 +1,7 @@
 use logos::{Logos, SpannedIter};
 
 use std::{
+    collections::HashSet,
     convert::TryFrom,
     fmt::{Display, Formatter, Result as FmtResult},
 };
@@ -12,33 +13,109 @@ mod raw;
 use raw::RawToken;
 
 #[derive(Debug, Clone)]
-
 pub enum LexError {
     Internal,
     Custom(&'static str),
+    /// A digit that is not valid in the base of the integer containing it.
+    InvalidDigit {
+        /// Position of the digit inside the integer.
+        offset: usize,
+        digit: char,
+        radix: u32,
+    },
 }
 
+/// The built-in operators made of characters that can be used in user-defined operators.
+const BUILTIN_OPS: [&str; 20] = [
+    "+", "-", "*", "**", "/", "%", "&", "|", "^", ">>", "<<", "&&", "||", "==", ">", "<", ">=",
+    "<=", "=", "->",
+];
+
 pub struct Lexer<'a> {
+    input: &'a str,
     inner: SpannedIter<'a, RawToken<'a>>,
+    /// Position of the start of the input of `inner` inside `input`.
+    offset: usize,
+    /// User-defined operators, which are the ones written between parentheses somewhere in the
+    /// input.
+    declared: HashSet<&'a str>,
 }
 
 impl<'a> Lexer<'a> {
     pub fn from_input(input: &'a str) -> Self {
+        let raw: Vec<_> = RawToken::lexer(input).collect();
+        let declared = raw
+            .windows(3)
+            .filter_map(|window| match window {
+                [RawToken::LParen, RawToken::CustomOp(op), RawToken::RParen] => Some(*op),
+                _ => None,
+            })
+            .collect();
+
         Lexer {
+            input,
             inner: RawToken::lexer(input).spanned(),
+            offset: 0,
+            declared,
         }
     }
+
+    /// Splits a run of operator characters that is not a user-defined operator.
+    ///
+    /// The longest built-in or user-defined operator at the start of the run is returned and the
+    /// lexer continues right after it, so `x=-1` is lexed as `x`, `=` and `-1`.
+    fn split_op(&mut self, op: &'a str, start: usize) -> Option<(RawToken<'a>, usize)> {
+        let len = BUILTIN_OPS
+            .iter()
+            .chain(self.declared.iter())
+            .filter(|prefix| op.starts_with(*prefix))
+            .map(|prefix| prefix.len())
+            .max()?;
+        let end = start + len;
+        self.inner = RawToken::lexer(&self.input[end..]).spanned();
+        self.offset = end;
+
+        let prefix = &op[..len];
+        let raw = if self.declared.contains(prefix) {
+            RawToken::CustomOp(prefix)
+        } else {
+            RawToken::lexer(prefix).next()?
+        };
+        Some((raw, end))
+    }
 }
 
 impl<'a> Iterator for Lexer<'a> {
     type Item = Result<(usize, Token<'a>, usize), Located<LexError>>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let (raw, span) = self.inner.next()?;
+        let (mut raw, span) = self.inner.next()?;
+        let start = self.offset + span.start;
+        let mut end = self.offset + span.end;
+
+        if let RawToken::CustomOp(op) = raw {
+            if !self.declared.contains(op) {
+                if let Some((builtin, builtin_end)) = self.split_op(op, start) {
+                    raw = builtin;
+                    end = builtin_end;
+                }
+            }
+        }
+
         Some(
             Token::try_from(raw)
-                .map(|token| (span.start, token, span.end))
-                .map_err(|err| Location::new(span.start, span.end).with_content(err)),
+                .map(|token| (start, token, end))
+                .map_err(|err| {
+                    let loc = match err {
+                        // Point to the invalid digit instead of the whole integer.
+                        LexError::InvalidDigit { offset, digit, .. } => {
+                            let start = start + offset;
+                            Location::new(start, start + digit.len_utf8())
+                        }
+                        _ => Location::new(start, end),
+                    };
+                    loc.with_content(err)
+                }),
         )
     }
 }
@@ -50,9 +127,23 @@ pub enum Token<'a> {
     Ident(&'a str),
     Kword(Keyword),
     Op(Operator),
+    CustomOp(&'a str),
     Sym(Symbol),
 }
 
+impl<'a> Token<'a> {
+    pub fn kind(&self) -> TokenKind {
+        match self {
+            Token::Newline => TokenKind::Newline,
+            Token::Int(_) => TokenKind::Number,
+            Token::Ident(_) => TokenKind::Name,
+            Token::Kword(_) => TokenKind::Keyword,
+            Token::Op(_) | Token::CustomOp(_) => TokenKind::Operator,
+            Token::Sym(_) => TokenKind::Symbol,
+        }
+    }
+}
+
 impl<'a> Display for Token<'a> {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
         match self {
@@ -61,11 +152,25 @@ impl<'a> Display for Token<'a> {
             Token::Ident(ident) => write!(f, "{}", ident),
             Token::Kword(kw) => write!(f, "{}", kw),
             Token::Op(op) => write!(f, "{}", op),
+            Token::CustomOp(op) => write!(f, "{}", op),
             Token::Sym(sym) => write!(f, "{}", sym),
         }
     }
 }
 
+/// The kind of a token, without any of its contents.
+///
+/// This is meant to be used by tools that only need to classify tokens, like syntax highlighters.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TokenKind {
+    Newline,
+    Number,
+    Name,
+    Keyword,
+    Operator,
+    Symbol,
+}
+
 impl<'a> TryFrom<RawToken<'a>> for Token<'a> {
     type Error = LexError;
 
@@ -74,12 +179,14 @@ impl<'a> TryFrom<RawToken<'a>> for Token<'a> {
             RawToken::Newline => Ok(Token::Newline),
             RawToken::Int(int) => Ok(Token::Int(int)),
             RawToken::Ident(ident) => Ok(Token::Ident(ident)),
+            RawToken::CustomOp(op) => Ok(Token::CustomOp(op)),
             RawToken::Fn => Ok(Token::Kword(Keyword::Fn)),
             RawToken::If => Ok(Token::Kword(Keyword::If)),
             RawToken::Do => Ok(Token::Kword(Keyword::Do)),
             RawToken::End => Ok(Token::Kword(Keyword::End)),
             RawToken::Else => Ok(Token::Kword(Keyword::Else)),
             RawToken::Elif => Ok(Token::Kword(Keyword::Elif)),
+            RawToken::Rec => Ok(Token::Kword(Keyword::Rec)),
             RawToken::True => Ok(Token::Kword(Keyword::True)),
             RawToken::False => Ok(Token::Kword(Keyword::False)),
             RawToken::Unit => Ok(Token::Kword(Keyword::Unit)),
@@ -87,9 +194,15 @@ impl<'a> TryFrom<RawToken<'a>> for Token<'a> {
             RawToken::BoolTy => Ok(Token::Kword(Keyword::BoolTy)),
             RawToken::UnitTy => Ok(Token::Kword(Keyword::UnitTy)),
             RawToken::Print => Ok(Token::Kword(Keyword::Print)),
+            RawToken::Neg => Ok(Token::Kword(Keyword::Neg)),
+            RawToken::Dbg => Ok(Token::Kword(Keyword::Dbg)),
+            RawToken::NotFn => Ok(Token::Kword(Keyword::Not)),
+            RawToken::Ignore => Ok(Token::Kword(Keyword::Ignore)),
+            RawToken::ReadInt => Ok(Token::Kword(Keyword::ReadInt)),
             RawToken::Add => Ok(Token::Op(Operator::Add)),
             RawToken::Sub => Ok(Token::Op(Operator::Sub)),
             RawToken::Mul => Ok(Token::Op(Operator::Mul)),
+            RawToken::Pow => Ok(Token::Op(Operator::Pow)),
             RawToken::Div => Ok(Token::Op(Operator::Div)),
             RawToken::Rem => Ok(Token::Op(Operator::Rem)),
             RawToken::BitAnd => Ok(Token::Op(Operator::BitAnd)),
@@ -112,6 +225,14 @@ impl<'a> TryFrom<RawToken<'a>> for Token<'a> {
             RawToken::LParen => Ok(Token::Sym(Symbol::LParen)),
             RawToken::RParen => Ok(Token::Sym(Symbol::RParen)),
             RawToken::Comma => Ok(Token::Sym(Symbol::Comma)),
+            RawToken::Semicolon => Ok(Token::Sym(Symbol::Semicolon)),
+            RawToken::Backtick => Ok(Token::Sym(Symbol::Backtick)),
+            RawToken::Underscore => Ok(Token::Sym(Symbol::Underscore)),
+            RawToken::InvalidDigit((offset, digit, radix)) => Err(LexError::InvalidDigit {
+                offset,
+                digit,
+                radix,
+            }),
             RawToken::Error => Err(LexError::Internal),
         }
     }
@@ -125,6 +246,7 @@ pub enum Keyword {
     End,
     Else,
     Elif,
+    Rec,
     True,
     False,
     Unit,
@@ -132,6 +254,11 @@ pub enum Keyword {
     BoolTy,
     UnitTy,
     Print,
+    Neg,
+    Dbg,
+    Not,
+    Ignore,
+    ReadInt,
 }
 
 impl Display for Keyword {
@@ -143,6 +270,7 @@ impl Display for Keyword {
             Keyword::End => write!(f, "end"),
             Keyword::Else => write!(f, "else"),
             Keyword::Elif => write!(f, "elif"),
+            Keyword::Rec => write!(f, "rec"),
             Keyword::True => write!(f, "true"),
             Keyword::False => write!(f, "false"),
             Keyword::Unit => write!(f, "unit"),
@@ -150,6 +278,11 @@ impl Display for Keyword {
             Keyword::BoolTy => write!(f, "Bool"),
             Keyword::UnitTy => write!(f, "Unit"),
             Keyword::Print => write!(f, "print"),
+            Keyword::Neg => write!(f, "neg"),
+            Keyword::Dbg => write!(f, "dbg"),
+            Keyword::Not => write!(f, "not"),
+            Keyword::Ignore => write!(f, "ignore"),
+            Keyword::ReadInt => write!(f, "read_int"),
         }
     }
 }
@@ -161,6 +294,7 @@ pub enum Operator {
     Mul,
     Div,
     Rem,
+    Pow,
     BitAnd,
     BitOr,
     BitXor,
@@ -188,6 +322,7 @@ impl Display for Operator {
             Operator::Mul => write!(f, "*"),
             Operator::Div => write!(f, "/"),
             Operator::Rem => write!(f, "%"),
+            Operator::Pow => write!(f, "**"),
             Operator::BitAnd => write!(f, "&"),
             Operator::BitOr => write!(f, "|"),
             Operator::BitXor => write!(f, "^"),
@@ -214,6 +349,9 @@ pub enum Symbol {
     LParen,
     RParen,
     Comma,
+    Semicolon,
+    Backtick,
+    Underscore,
 }
 
 impl Display for Symbol {
@@ -222,6 +360,9 @@ impl Display for Symbol {
             Symbol::LParen => write!(f, "("),
             Symbol::RParen => write!(f, ")"),
             Symbol::Comma => write!(f, ","),
+            Symbol::Semicolon => write!(f, ";"),
+            Symbol::Backtick => write!(f, "`"),
+            Symbol::Underscore => write!(f, "_"),
         }
     }
 }
@@ -13,11 +13,21 @@ pub(super) enum RawToken<'a> {
     #[regex(r"-0b[0-1]+", |lex| lex_integer(lex.slice(), 2, true))]
     #[regex(r"0o[0-7]+", |lex| lex_integer(lex.slice(), 8, false))]
     #[regex(r"-0o[0-7]+", |lex| lex_integer(lex.slice(), 8, true))]
-    #[regex(r"0x[0-9a-f]+", |lex| lex_integer(lex.slice(), 16, false))]
-    #[regex(r"-0x[0-9a-f]+", |lex| lex_integer(lex.slice(), 16, true))]
+    #[regex(r"0x[0-9a-fA-F]+", |lex| lex_integer(lex.slice(), 16, false))]
+    #[regex(r"-0x[0-9a-fA-F]+", |lex| lex_integer(lex.slice(), 16, true))]
     Int(i64),
+    // These only match integers with at least one digit that is not valid in their base. They
+    // are always longer than the valid prefix of the integer, so they take precedence over it.
+    #[regex(r"-?0b[01]*[2-9a-zA-Z_][0-9a-zA-Z_]*", |lex| lex_invalid_digit(lex.slice(), 2))]
+    #[regex(r"-?0o[0-7]*[89a-zA-Z_][0-9a-zA-Z_]*", |lex| lex_invalid_digit(lex.slice(), 8))]
+    #[regex(r"-?0x[0-9a-fA-F]*[g-zG-Z_][0-9a-zA-Z_]*", |lex| lex_invalid_digit(lex.slice(), 16))]
+    InvalidDigit((usize, char, u32)),
     #[regex(r"[a-zA-Z][a-zA-Z0-9_]*")]
     Ident(&'a str),
+    // Operators defined by the user. The built-in operators take precedence over these, and runs
+    // that are not user-defined operators are split by `Lexer`.
+    #[regex(r"[<>=+\-*/%&|^~@$?]+")]
+    CustomOp(&'a str),
     #[token("fn")]
     Fn,
     #[token("if")]
@@ -30,6 +40,8 @@ pub(super) enum RawToken<'a> {
     Else,
     #[token("elif")]
     Elif,
+    #[token("rec")]
+    Rec,
     #[token("true")]
     True,
     #[token("false")]
@@ -44,12 +56,24 @@ pub(super) enum RawToken<'a> {
     UnitTy,
     #[token("print")]
     Print,
+    #[token("neg")]
+    Neg,
+    #[token("dbg")]
+    Dbg,
+    #[token("not")]
+    NotFn,
+    #[token("ignore")]
+    Ignore,
+    #[token("read_int")]
+    ReadInt,
     #[token("+")]
     Add,
     #[token("-")]
     Sub,
     #[token("*")]
     Mul,
+    #[token("**")]
+    Pow,
     #[token("/")]
     Div,
     #[token("%")]
@@ -94,11 +118,29 @@ pub(super) enum RawToken<'a> {
     Arrow,
     #[token(",")]
     Comma,
+    #[token(";")]
+    Semicolon,
+    #[token("`")]
+    Backtick,
+    #[token("_")]
+    Underscore,
     #[error]
     #[regex(r"[ \t]+", logos::skip)]
     Error,
 }
 
+/// Finds the first digit of an integer that is not valid in `radix`.
+///
+/// Returns the position of the digit inside `input`, the digit itself and the radix.
+fn lex_invalid_digit(input: &str, radix: u32) -> Option<(usize, char, u32)> {
+    // Skip the sign and the base prefix.
+    let start = input.find(['b', 'o', 'x'])? + 1;
+    input[start..]
+        .char_indices()
+        .find(|(_, c)| !c.is_digit(radix))
+        .map(|(index, c)| (start + index, c, radix))
+}
+
 fn lex_integer(mut input: &str, radix: u32, is_neg: bool) -> Option<i64> {
     // Stores how many characters we need to remove from the string to keep just the digits.
     let mut offset: usize = is_neg.into();
@@ -7,13 +7,16 @@ use pijama_ast::{
     node::Block,
 };
 
+mod desugar;
 mod lexer;
 lalrpop_mod!(
-    #[allow(unused_imports)]
+    #[allow(unused_imports, clippy::all)]
     parser
 );
 
-use lexer::{LexError, Lexer, Token};
+pub use lexer::{Keyword, Operator, Symbol, Token, TokenKind};
+
+use lexer::{LexError, Lexer};
 use parser::ProgParser;
 
 #[derive(Error, Debug, Eq, PartialEq)]
@@ -34,6 +37,13 @@ pub enum ParsingErrorKind {
     InvalidToken,
     #[error("Extra token")]
     ExtraToken,
+    #[error("`{digit}` is not a {base} digit")]
+    InvalidDigit { digit: char, base: &'static str },
+    #[error("Expected `end` to close the block")]
+    MissingEnd {
+        /// Location of the keyword that opened the block.
+        opened: Location,
+    },
     #[error("{0}")]
     Custom(&'static str),
 }
@@ -42,6 +52,10 @@ impl ParsingError {
     pub fn loc(&self) -> Location {
         self.loc
     }
+
+    pub fn kind(&self) -> &ParsingErrorKind {
+        &self.kind
+    }
 }
 
 impl<'a> From<ParseError<usize, Token<'a>, Located<LexError>>> for ParsingError {
@@ -74,26 +88,108 @@ impl<'a> From<ParseError<usize, Token<'a>, Located<LexError>>> for ParsingError
                 loc: Location::new(start, end),
                 kind: ParsingErrorKind::ExtraToken,
             },
-            ParseError::User { error } => {
-                let msg = match error.content {
-                    LexError::Internal => "Unrecognized token",
-                    LexError::Custom(msg) => msg,
-                };
-                ParsingError {
-                    loc: error.loc,
-                    kind: ParsingErrorKind::Custom(msg),
-                }
-            }
+            ParseError::User { error } => error.into(),
         }
     }
 }
 
-pub fn parse(input: &str) -> Result<Block, ParsingError> {
+impl From<Located<LexError>> for ParsingError {
+    fn from(error: Located<LexError>) -> Self {
+        let kind = match error.content {
+            LexError::Internal => ParsingErrorKind::Custom("Unrecognized token"),
+            LexError::Custom(msg) => ParsingErrorKind::Custom(msg),
+            LexError::InvalidDigit { digit, radix, .. } => ParsingErrorKind::InvalidDigit {
+                digit,
+                base: match radix {
+                    2 => "binary",
+                    8 => "octal",
+                    16 => "hexadecimal",
+                    _ => "decimal",
+                },
+            },
+        };
+        ParsingError {
+            loc: error.loc,
+            kind,
+        }
+    }
+}
+
+pub fn parse(input: &str) -> Result<Block<'_>, ParsingError> {
+    parse_file(input, 0)
+}
+
+/// Parses the source of the file with index `file`.
+///
+/// Every location of the returned block and of any returned error is inside that file.
+pub fn parse_file(input: &str, file: usize) -> Result<Block<'_>, ParsingError> {
     let lexer = Lexer::from_input(input);
-    let result = ProgParser::new().parse(input, lexer);
+    let result = ProgParser::new().parse(input, file, lexer);
 
-    match result {
-        Ok(block) => Ok(block),
-        Err(err) => Err(err.into()),
+    let error = match result {
+        Ok(block) => return Ok(block),
+        Err(ParseError::UnrecognizedEOF { location, expected })
+            if expected.iter().any(|token| token == "\"end\"") =>
+        {
+            match unclosed_block(input) {
+                Some(opened) => ParsingError {
+                    loc: Location::new(location, location),
+                    kind: ParsingErrorKind::MissingEnd {
+                        opened: opened.in_file(file),
+                    },
+                },
+                None => ParseError::UnrecognizedEOF { location, expected }.into(),
+            }
+        }
+        Err(err) => err.into(),
+    };
+
+    Err(ParsingError {
+        loc: error.loc.in_file(file),
+        ..error
+    })
+}
+
+/// Returns the location of the keyword opening the innermost block without an `end`.
+///
+/// Every `fn` and `if` opens a block that is closed by exactly one `end`. A `do` right after an
+/// `if` or `elif` opens the block of a condition, which is closed by its own `end`.
+fn unclosed_block(input: &str) -> Option<Location> {
+    let mut opened = Vec::new();
+    let mut after_cond_keyword = false;
+    for result in Lexer::from_input(input) {
+        let (start, token, end) = result.ok()?;
+        match token {
+            Token::Kword(Keyword::Fn) | Token::Kword(Keyword::If) => {
+                opened.push(Location::new(start, end))
+            }
+            Token::Kword(Keyword::Do) if after_cond_keyword => {
+                opened.push(Location::new(start, end))
+            }
+            Token::Kword(Keyword::End) => {
+                opened.pop()?;
+            }
+            _ => (),
+        }
+        match token {
+            // Conditions can start on the line after their keyword.
+            Token::Newline => (),
+            Token::Kword(Keyword::If) | Token::Kword(Keyword::Elif) => after_cond_keyword = true,
+            _ => after_cond_keyword = false,
+        }
     }
+    opened.pop()
+}
+
+/// Splits the input into tokens without parsing it.
+///
+/// This is a separate pass from `parse` and it is intended to be used by tools that only care
+/// about the lexical structure of a program, such as syntax highlighters.
+pub fn tokenize(input: &str) -> Result<Vec<Located<Token<'_>>>, ParsingError> {
+    Lexer::from_input(input)
+        .map(|result| {
+            let (start, token, end) = result?;
+            Ok(Location::new(start, end).with_content(token))
+        })
+        .collect()
 }
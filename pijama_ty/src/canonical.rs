@@ -0,0 +1,113 @@
+//! Compact canonical representation of types.
+//!
+//! Unlike `Display`, this representation does not depend on how types are written by humans. It
+//! uses prefix notation so it does not need parentheses or whitespace:
+//!
+//! - `b`, `i` and `u` stand for `Bool`, `Int` and `Unit`.
+//! - `f` followed by two types stands for a function between them.
+//! - `v` followed by a decimal index stands for the type variable with that index.
+//!
+//! For example, `(Int -> ?X3) -> Bool` is represented as `ffiv3b`.
+use thiserror::Error;
+
+use crate::{Ty, TyVisitor};
+
+#[derive(Error, Debug, Eq, PartialEq)]
+pub enum CanonicalError {
+    #[error("Unexpected end of input")]
+    UnexpectedEnd,
+    #[error("Unexpected character `{1}` at position {0}")]
+    UnexpectedChar(usize, char),
+    #[error("Invalid type variable index at position {0}")]
+    InvalidIndex(usize),
+}
+
+impl Ty {
+    /// Returns the canonical representation of the type.
+    pub fn to_canonical(&self) -> String {
+        let mut writer = Writer::default();
+        writer.visit_ty(self);
+        writer.buf
+    }
+
+    /// Parses a type from its canonical representation.
+    ///
+    /// The whole input must be a single type.
+    pub fn from_canonical(input: &str) -> Result<Self, CanonicalError> {
+        let mut parser = Parser { input, pos: 0 };
+        let ty = parser.parse()?;
+        match parser.peek() {
+            Some(c) => Err(CanonicalError::UnexpectedChar(parser.pos, c)),
+            None => Ok(ty),
+        }
+    }
+}
+
+/// Visitor writing the canonical representation of a type.
+#[derive(Default)]
+struct Writer {
+    buf: String,
+}
+
+impl TyVisitor for Writer {
+    fn visit_bool(&mut self) {
+        self.buf.push('b');
+    }
+
+    fn visit_int(&mut self) {
+        self.buf.push('i');
+    }
+
+    fn visit_unit(&mut self) {
+        self.buf.push('u');
+    }
+
+    fn visit_arrow(&mut self, t1: &Ty, t2: &Ty) {
+        self.buf.push('f');
+        self.super_arrow(t1, t2);
+    }
+
+    fn visit_var(&mut self, index: usize) {
+        self.buf.push('v');
+        self.buf.push_str(&index.to_string());
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    /// Byte position of the next character.
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn parse(&mut self) -> Result<Ty, CanonicalError> {
+        let c = self.peek().ok_or(CanonicalError::UnexpectedEnd)?;
+        let start = self.pos;
+        self.pos += c.len_utf8();
+        match c {
+            'b' => Ok(Ty::Bool),
+            'i' => Ok(Ty::Int),
+            'u' => Ok(Ty::Unit),
+            'f' => {
+                let t1 = self.parse()?;
+                let t2 = self.parse()?;
+                Ok(Ty::Arrow(Box::new(t1), Box::new(t2)))
+            }
+            'v' => {
+                let digits = self.input[self.pos..]
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(self.input.len() - self.pos);
+                let index = self.input[self.pos..self.pos + digits]
+                    .parse()
+                    .map_err(|_| CanonicalError::InvalidIndex(start))?;
+                self.pos += digits;
+                Ok(Ty::Var(index))
+            }
+            c => Err(CanonicalError::UnexpectedChar(start, c)),
+        }
+    }
+}
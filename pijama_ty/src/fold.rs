@@ -0,0 +1,66 @@
+//! Trait to transform types.
+use crate::Ty;
+
+/// Trait for the type folder pattern.
+///
+/// This trait should be used when you need to rebuild a type replacing some parts of it without
+/// writing the code necessary to traverse the type yourself. Use `TyVisitor` if you only need to
+/// collect information about the type.
+///
+/// There are two kinds of methods:
+/// - The `fold_<foo>` methods: where the code specific to your transformation resides.
+/// - The `super_<foo>` methods: that destructure each component and take care of the actual
+///   folding.
+///
+/// By default, every `fold_<foo>` method rebuilds the same type it was given. An implementation
+/// of `fold_ty` that does not handle the type by itself should return the result of `super_ty`
+/// to guarantee that the whole type is folded.
+///
+/// Every update to the `Ty` type should be reflected here too.
+pub trait TyFolder {
+    /// Destructures a type to fold its children.
+    fn super_ty(&mut self, ty: Ty) -> Ty {
+        match ty {
+            Ty::Bool | Ty::Int | Ty::Unit => ty,
+            Ty::Arrow(t1, t2) => self.fold_arrow(*t1, *t2),
+            Ty::Var(index) => self.fold_var(index),
+        }
+    }
+    /// Folds a type.
+    fn fold_ty(&mut self, ty: Ty) -> Ty {
+        self.super_ty(ty)
+    }
+    /// Folds a function type.
+    fn fold_arrow(&mut self, t1: Ty, t2: Ty) -> Ty {
+        let t1 = self.fold_ty(t1);
+        let t2 = self.fold_ty(t2);
+        Ty::Arrow(Box::new(t1), Box::new(t2))
+    }
+    /// Folds a type variable.
+    fn fold_var(&mut self, index: usize) -> Ty {
+        Ty::Var(index)
+    }
+}
+
+/// Folder replacing a type variable by a type.
+struct Substitute<'t> {
+    index: usize,
+    new: &'t Ty,
+}
+
+impl TyFolder for Substitute<'_> {
+    fn fold_var(&mut self, index: usize) -> Ty {
+        if index == self.index {
+            self.new.clone()
+        } else {
+            Ty::Var(index)
+        }
+    }
+}
+
+impl Ty {
+    /// Replaces every occurrence of the type variable with index `index` by `new`.
+    pub fn substitute(self, index: usize, new: &Ty) -> Ty {
+        Substitute { index, new }.fold_ty(self)
+    }
+}
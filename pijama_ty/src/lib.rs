@@ -4,10 +4,19 @@
 //! type-checker.
 use std::fmt;
 
-use pijama_ast::ty::Ty as TyAST;
+mod canonical;
+mod fold;
+mod visit;
+
+pub use canonical::CanonicalError;
+pub use fold::TyFolder;
+pub use visit::TyVisitor;
+
+use pijama_ast::{node::Literal, ty::Ty as TyAST};
 
 /// A type used by the type-checker.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Ty {
     /// The type of booleans.
     Bool,
@@ -22,40 +31,59 @@ pub enum Ty {
 }
 
 impl Ty {
-    /// Checks if the index of a `Ty::Var` is contained inside the type.
-    pub fn contains(&self, index: usize) -> bool {
+    /// Checks if the type is a function type.
+    pub fn is_arrow(&self) -> bool {
+        self.as_arrow().is_some()
+    }
+
+    /// Returns the argument and return types if the type is a function type.
+    pub fn as_arrow(&self) -> Option<(&Ty, &Ty)> {
+        match self {
+            Ty::Arrow(ty1, ty2) => Some((ty1, ty2)),
+            _ => None,
+        }
+    }
+
+    /// Returns a placeholder value of this type.
+    ///
+    /// This is `0` for `Int`, `false` for `Bool` and `unit` for `Unit`. Functions and type
+    /// variables do not have a placeholder value.
+    pub fn default_value(&self) -> Option<Literal> {
         match self {
-            Ty::Bool | Ty::Int | Ty::Unit => false,
-            Ty::Arrow(ty1, ty2) => ty1.contains(index) || ty2.contains(index),
-            Ty::Var(inner) => *inner == index,
+            Ty::Bool => Some(Literal::Bool(false)),
+            Ty::Int => Some(Literal::Number(0)),
+            Ty::Unit => Some(Literal::Unit),
+            Ty::Arrow(_, _) | Ty::Var(_) => None,
         }
     }
 }
 
 impl fmt::Display for Ty {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        use Ty::*;
-        match self {
-            Bool => write!(f, "Bool"),
-            Int => write!(f, "Int"),
-            Unit => write!(f, "Unit"),
-            Arrow(t1, t2) => {
-                if let Arrow(_, _) = t1.as_ref() {
-                    write!(f, "({}) -> {}", t1, t2)
-                } else {
-                    write!(f, "{} -> {}", t1, t2)
-                }
-            }
-            Var(index) => write!(f, "?X{}", index),
-        }
+        let mut printer = Printer::default();
+        printer.visit_ty(self);
+        f.write_str(&printer.buf)
     }
 }
 
 impl Ty {
+    /// Displays the type renaming its type variables to `a`, `b`, `c` and so on, in order of
+    /// appearance.
+    ///
+    /// After `z`, the variables are named `a1`, `b1` and so on.
+    pub fn display_normalized(&self) -> String {
+        let mut printer = Printer {
+            vars: Some(Vec::new()),
+            ..Printer::default()
+        };
+        printer.visit_ty(self);
+        printer.buf
+    }
+
     pub fn from_ast(ty_ast: TyAST) -> Option<Self> {
         match ty_ast {
-            // FIXME: In general we should translate missing types into type variables inside
-            // `ty_check::Context`.
+            // Missing types are translated into type variables during lowering when they can be
+            // inferred.
             TyAST::Missing => None,
             TyAST::Bool => Some(Ty::Bool),
             TyAST::Int => Some(Ty::Int),
@@ -67,3 +95,58 @@ impl Ty {
         }
     }
 }
+
+/// Visitor writing the human readable representation of a type.
+#[derive(Default)]
+struct Printer {
+    buf: String,
+    /// Holds the indices of the type variables found so far if they must be normalized.
+    vars: Option<Vec<usize>>,
+}
+
+impl TyVisitor for Printer {
+    fn visit_bool(&mut self) {
+        self.buf.push_str("Bool");
+    }
+
+    fn visit_int(&mut self) {
+        self.buf.push_str("Int");
+    }
+
+    fn visit_unit(&mut self) {
+        self.buf.push_str("Unit");
+    }
+
+    fn visit_arrow(&mut self, t1: &Ty, t2: &Ty) {
+        if t1.is_arrow() {
+            self.buf.push('(');
+            self.visit_ty(t1);
+            self.buf.push(')');
+        } else {
+            self.visit_ty(t1);
+        }
+        self.buf.push_str(" -> ");
+        self.visit_ty(t2);
+    }
+
+    fn visit_var(&mut self, index: usize) {
+        let vars = match &mut self.vars {
+            Some(vars) => vars,
+            None => {
+                self.buf.push_str(&format!("?X{}", index));
+                return;
+            }
+        };
+        let pos = match vars.iter().position(|&var| var == index) {
+            Some(pos) => pos,
+            None => {
+                vars.push(index);
+                vars.len() - 1
+            }
+        };
+        self.buf.push((b'a' + (pos % 26) as u8) as char);
+        if pos >= 26 {
+            self.buf.push_str(&(pos / 26).to_string());
+        }
+    }
+}
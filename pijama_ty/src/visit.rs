@@ -0,0 +1,109 @@
+//! Trait to traverse types.
+use crate::Ty;
+
+/// Trait for the type visitor pattern.
+///
+/// This trait should be used when you need to collect information about some parts of a type
+/// without rebuilding it. Unlike `TyFolder`, it borrows the type instead of taking ownership of
+/// it.
+///
+/// There are two kinds of methods:
+/// - The `visit_<foo>` methods: where the code specific to your traversal resides.
+/// - The `super_<foo>` methods: that destructure each component and take care of the actual
+///   traversal.
+///
+/// By default, every `visit_<foo>` method visits the children of the type it was given, if any.
+/// An implementation of `visit_ty` that does not handle the type by itself should call
+/// `super_ty` to guarantee that the whole type is visited.
+///
+/// Every update to the `Ty` type should be reflected here too.
+pub trait TyVisitor {
+    /// Destructures a type to visit its children.
+    fn super_ty(&mut self, ty: &Ty) {
+        match ty {
+            Ty::Bool => self.visit_bool(),
+            Ty::Int => self.visit_int(),
+            Ty::Unit => self.visit_unit(),
+            Ty::Arrow(t1, t2) => self.visit_arrow(t1, t2),
+            Ty::Var(index) => self.visit_var(*index),
+        }
+    }
+    /// Visits both sides of a function type.
+    fn super_arrow(&mut self, t1: &Ty, t2: &Ty) {
+        self.visit_ty(t1);
+        self.visit_ty(t2);
+    }
+    /// Visits a type.
+    fn visit_ty(&mut self, ty: &Ty) {
+        self.super_ty(ty)
+    }
+    /// Visits the boolean type.
+    fn visit_bool(&mut self) {}
+    /// Visits the integer type.
+    fn visit_int(&mut self) {}
+    /// Visits the unit type.
+    fn visit_unit(&mut self) {}
+    /// Visits a function type.
+    fn visit_arrow(&mut self, t1: &Ty, t2: &Ty) {
+        self.super_arrow(t1, t2)
+    }
+    /// Visits a type variable.
+    fn visit_var(&mut self, _index: usize) {}
+}
+
+/// Visitor collecting the indices of the type variables in order of appearance.
+#[derive(Default)]
+struct FreeVars {
+    vars: Vec<usize>,
+}
+
+impl TyVisitor for FreeVars {
+    fn visit_var(&mut self, index: usize) {
+        if !self.vars.contains(&index) {
+            self.vars.push(index);
+        }
+    }
+}
+
+/// Visitor counting the arrows of a type that are not inside the type of an argument.
+#[derive(Default)]
+struct Arity {
+    arity: usize,
+}
+
+impl TyVisitor for Arity {
+    fn visit_arrow(&mut self, _t1: &Ty, t2: &Ty) {
+        self.arity += 1;
+        self.visit_ty(t2);
+    }
+}
+
+impl Ty {
+    /// Returns the indices of the type variables inside the type, in order of appearance and
+    /// without repetitions.
+    pub fn free_vars(&self) -> Vec<usize> {
+        let mut visitor = FreeVars::default();
+        visitor.visit_ty(self);
+        visitor.vars
+    }
+
+    /// Checks if the index of a `Ty::Var` is contained inside the type.
+    pub fn contains(&self, index: usize) -> bool {
+        self.free_vars().contains(&index)
+    }
+
+    /// Checks if the type does not contain any `Ty::Var`.
+    pub fn is_concrete(&self) -> bool {
+        self.free_vars().is_empty()
+    }
+
+    /// Returns the number of arguments a value of this type can take.
+    ///
+    /// This is the number of arrows in the type that are not inside the type of an argument.
+    /// Every type that is not a function has arity zero.
+    pub fn arity(&self) -> usize {
+        let mut visitor = Arity::default();
+        visitor.visit_ty(self);
+        visitor.arity
+    }
+}
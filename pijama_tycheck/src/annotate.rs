@@ -0,0 +1,113 @@
+//! Terms annotated with the type of each one of their subterms.
+use pijama_ast::{
+    location::{Located, Location},
+    node::{BinOp, Literal, Name, Primitive, UnOp},
+};
+
+use pijama_mir::{LetKind, Term};
+use pijama_ty::Ty;
+
+/// A term annotated with its type.
+///
+/// The subterms of the term are annotated too, so each node of the term carries its own type.
+#[derive(Debug, Clone)]
+pub struct AnnotatedTerm<'a> {
+    pub loc: Location,
+    pub ty: Ty,
+    pub kind: AnnotatedKind<'a>,
+}
+
+/// The kinds of annotated terms. There is one kind for each variant of the MIR's `Term`.
+#[derive(Debug, Clone)]
+pub enum AnnotatedKind<'a> {
+    Var(Name<'a>),
+    Abs(Name<'a>, Ty, Box<AnnotatedTerm<'a>>),
+    UnaryOp(UnOp, Box<AnnotatedTerm<'a>>),
+    BinaryOp(BinOp, Box<AnnotatedTerm<'a>>, Box<AnnotatedTerm<'a>>),
+    App(Box<AnnotatedTerm<'a>>, Box<AnnotatedTerm<'a>>),
+    Lit(Literal),
+    Cond(
+        Box<AnnotatedTerm<'a>>,
+        Box<AnnotatedTerm<'a>>,
+        Box<AnnotatedTerm<'a>>,
+    ),
+    Let(
+        LetKind,
+        Located<Name<'a>>,
+        Box<AnnotatedTerm<'a>>,
+        Box<AnnotatedTerm<'a>>,
+    ),
+    Seq(Box<AnnotatedTerm<'a>>, Box<AnnotatedTerm<'a>>),
+    PrimFn(Primitive),
+}
+
+impl<'a> AnnotatedTerm<'a> {
+    /// Annotates `term` with `ty`, taking the annotated subterms of `term` from the end of
+    /// `annotated`.
+    ///
+    /// The subterms must have been pushed into `annotated` in the same order they appear inside
+    /// `term`. This function panics if any of them is missing.
+    pub(crate) fn from_subterms(
+        term: &Located<Term<'a>>,
+        ty: Ty,
+        annotated: &mut Vec<AnnotatedTerm<'a>>,
+    ) -> Self {
+        let mut pop = || Box::new(annotated.pop().expect("Subterm was not typed"));
+
+        let kind = match &term.content {
+            Term::Var(name) => AnnotatedKind::Var(*name),
+            Term::Abs(name, ty, _) => AnnotatedKind::Abs(*name, ty.clone(), pop()),
+            Term::UnaryOp(op, _) => AnnotatedKind::UnaryOp(*op, pop()),
+            Term::BinaryOp(op, _, _) => {
+                let t2 = pop();
+                AnnotatedKind::BinaryOp(*op, pop(), t2)
+            }
+            Term::App(_, _) => {
+                let t2 = pop();
+                AnnotatedKind::App(pop(), t2)
+            }
+            Term::Lit(lit) => AnnotatedKind::Lit(*lit),
+            Term::Cond(_, _, _) => {
+                let t3 = pop();
+                let t2 = pop();
+                AnnotatedKind::Cond(pop(), t2, t3)
+            }
+            Term::Let(kind, name, _, _) => {
+                let t2 = pop();
+                AnnotatedKind::Let(kind.clone(), *name, pop(), t2)
+            }
+            Term::Seq(_, _) => {
+                let t2 = pop();
+                AnnotatedKind::Seq(pop(), t2)
+            }
+            Term::PrimFn(prim) => AnnotatedKind::PrimFn(*prim),
+        };
+
+        AnnotatedTerm {
+            loc: term.loc,
+            ty,
+            kind,
+        }
+    }
+
+    /// Applies `f` over the type of the term and the types of all its subterms.
+    pub(crate) fn map_tys(&mut self, f: &mut impl FnMut(&mut Ty)) {
+        f(&mut self.ty);
+        match &mut self.kind {
+            AnnotatedKind::Var(_) | AnnotatedKind::Lit(_) | AnnotatedKind::PrimFn(_) => (),
+            AnnotatedKind::Abs(_, _, t1) | AnnotatedKind::UnaryOp(_, t1) => t1.map_tys(f),
+            AnnotatedKind::BinaryOp(_, t1, t2)
+            | AnnotatedKind::App(t1, t2)
+            | AnnotatedKind::Let(_, _, t1, t2)
+            | AnnotatedKind::Seq(t1, t2) => {
+                t1.map_tys(f);
+                t2.map_tys(f);
+            }
+            AnnotatedKind::Cond(t1, t2, t3) => {
+                t1.map_tys(f);
+                t2.map_tys(f);
+                t3.map_tys(f);
+            }
+        }
+    }
+}
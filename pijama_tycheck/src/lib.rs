@@ -6,36 +6,97 @@
 //!
 //! The entry-point for this module is the `ty_check` method which does the type checking of a
 //! whole program. However, most of the heavy lifting is done by the `Context` and `Unifier` types.
+//!
+//! The `Context` type-checks terms bidirectionally: when a term is expected to have a type, like
+//! the value of an annotated binding, that type is propagated down into the term. Terms without
+//! an expected type have their types synthesized instead.
 use pijama_ast::{
     location::{Located, Location},
     node::{BinOp, Literal, Name, Primitive, UnOp},
 };
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 
 use pijama_mir::{LetKind, Term};
 use pijama_ty::Ty;
 
+mod annotate;
 mod result;
 mod unify;
 
+pub use annotate::{AnnotatedKind, AnnotatedTerm};
 pub use result::{TyError, TyResult};
+pub use unify::UnifyWith;
 use unify::{Constraint, Unifier};
 
+/// Hint for errors caused by a condition that is not a `Bool`.
+const COND_HINT: &str = "conditions must be `Bool`, did you forget a comparison?";
+/// Hint for errors caused by an operand of `&&` or `||` that is not a `Bool`.
+const LOGIC_OP_HINT: &str = "`&&` and `||` only take `Bool`s, did you mean `&` or `|`?";
+
 /// Function that type-checks a term and returns its type.
 ///
 /// This function must always be called in the "root" term of the program. Otherwise, the type
 /// checker might not have all the bindings required to do its job.
 pub fn ty_check(term: &Located<Term<'_>>) -> TyResult<Located<Ty>> {
+    ty_check_with_prints(term).map(|(ty, _)| ty)
+}
+
+/// Function that type-checks a term and returns its type together with the types of the values
+/// printed by it.
+///
+/// The types of the printed values are indexed by the location of each `print` primitive inside
+/// the term.
+pub fn ty_check_with_prints(
+    term: &Located<Term<'_>>,
+) -> TyResult<(Located<Ty>, HashMap<Location, Ty>)> {
     // Create a new, empty context.
-    let mut ctx = Context::default();
+    let mut ctx = Context::for_term(term);
     // Obtain typing constraints and the type of `term`.
-    let mut ty = ctx.type_of(&term)?;
+    let mut ty = ctx.type_of(term)?;
+    let prints = std::mem::take(&mut ctx.prints);
     // Solve the constraints using unification.
     let unif = Unifier::from_ctx(ctx)?;
-    // Apply the substitutions found during unification over the type of `term`.
+    // Apply the substitutions found during unification over the type of `term` and the types of
+    // the printed values.
     unif.replace(&mut ty.content);
-    Ok(ty)
+    let prints = prints
+        .into_iter()
+        .map(|(loc, mut ty)| {
+            unif.replace(&mut ty);
+            (loc, ty)
+        })
+        .collect();
+    Ok((ty, prints))
+}
+
+/// Function that type-checks a term and returns it with every subterm annotated with its type.
+///
+/// The annotated types are the ones found after unification, so they can only have type
+/// variables if those variables could not be resolved.
+pub fn annotate<'a>(term: &Located<Term<'a>>) -> TyResult<AnnotatedTerm<'a>> {
+    let mut ctx = Context {
+        annotated: Some(Vec::new()),
+        ..Context::for_term(term)
+    };
+    ctx.type_of(term)?;
+    let mut annotated = ctx.annotated.take().unwrap().pop().unwrap();
+    let unif = Unifier::from_ctx(ctx)?;
+    annotated.map_tys(&mut |ty| unif.replace(ty));
+    Ok(annotated)
+}
+
+/// Function that type-checks a term and returns its type, rejecting ambiguous types.
+///
+/// This function behaves like `ty_check` but it returns an error if the type of the term has
+/// type variables that could not be resolved during unification.
+pub fn ty_check_strict(term: &Located<Term<'_>>) -> TyResult<Located<Ty>> {
+    let ty = ty_check(term)?;
+    if ty.content.is_concrete() {
+        Ok(ty)
+    } else {
+        Err(TyError::AmbiguousType(ty))
+    }
 }
 
 /// A type binding.
@@ -70,9 +131,39 @@ struct Context<'a> {
     /// Each typing constraint is introduced by a particular `type_of_*` method with a suitable
     /// location in case an error needs to be returned.
     constraints: VecDeque<Located<Constraint>>,
+    /// Types of the arguments of each `print` primitive, stored along the location of the
+    /// primitive.
+    prints: Vec<(Location, Ty)>,
+    /// Annotated subterms whose parent term has not been typed yet, in the order they were typed.
+    /// Terms are only annotated if this is not `None`.
+    ///
+    /// Every term is typed after its subterms, so when a term is typed its annotated subterms are
+    /// at the end of this stack.
+    annotated: Option<Vec<AnnotatedTerm<'a>>>,
+    /// Types of the recursive bindings without a type annotation, stored along the location of
+    /// their names.
+    inferred_recs: Vec<Located<Ty>>,
+    /// Types of the first operands of `&` and `|`, stored along their locations. These operators
+    /// are overloaded, so each type must be resolved to either `Int` or `Bool`.
+    overloaded_ops: Vec<Located<Ty>>,
+    /// Types of the operands of ordering comparisons, stored along their locations. Their
+    /// constraints are added after unification to distinguish the operands that cannot be
+    /// compared from the ones that are not `Int`s.
+    compared_ops: Vec<Located<Ty>>,
 }
 
 impl<'a> Context<'a> {
+    /// Returns an empty context to type-check `term`.
+    ///
+    /// The type annotations of `term` can have type variables in place of the annotations that
+    /// were missing in the source code. The variables created by this context start after them.
+    fn for_term(term: &Located<Term<'a>>) -> Self {
+        Context {
+            count: vars_end(&term.content),
+            ..Context::default()
+        }
+    }
+
     /// Returns a new type variable.
     ///
     /// This variable is guaranteed to be different from all the other types introduced before.
@@ -90,7 +181,21 @@ impl<'a> Context<'a> {
     /// has a location that will be used as the location of the error if the constraint is
     /// impossible to satisfy.
     pub fn add_constraint(&mut self, expected: Ty, found: Ty, loc: Location) {
-        let constr = Constraint::new(expected, found);
+        self.add_constraint_with_hint(expected, found, loc, None)
+    }
+
+    /// Adds a new `Constraint` with a hint for the error returned if it cannot be satisfied.
+    ///
+    /// Hints are meant to help with common mistakes, so they are only added to the error if an
+    /// `Int` is found where a `Bool` was expected.
+    pub fn add_constraint_with_hint(
+        &mut self,
+        expected: Ty,
+        found: Ty,
+        loc: Location,
+        hint: Option<&'static str>,
+    ) {
+        let constr = Constraint::new(expected, found).with_hint(hint);
         // New constraints are front-pushed because the `Unifier` processes constraints by popping
         // them from the back. If we just back-push the constraints, we end up taking care of the
         // newer constraints first, which are more complex and can end up in less readable type
@@ -108,7 +213,7 @@ impl<'a> Context<'a> {
     /// method) as unification has not taken place yet.
     fn type_of(&mut self, term: &Located<Term<'a>>) -> TyResult<Located<Ty>> {
         let loc = term.loc;
-        match &term.content {
+        let ty = match &term.content {
             Term::Lit(lit) => self.type_of_lit(loc, lit),
             Term::Var(name) => self.type_of_var(loc, name),
             Term::Abs(name, ty, body) => self.type_of_abs(loc, *name, ty, body.as_ref()),
@@ -123,6 +228,72 @@ impl<'a> Context<'a> {
             Term::Cond(t1, t2, t3) => self.type_of_cond(loc, t1.as_ref(), t2.as_ref(), t3.as_ref()),
             Term::Seq(t1, t2) => self.type_of_seq(loc, t1.as_ref(), t2.as_ref()),
             Term::PrimFn(prim) => self.type_of_prim_fn(loc, *prim),
+        }?;
+        self.annotate(term, &ty.content);
+        Ok(ty)
+    }
+
+    /// Checks that a term has the `expected` type and returns the type of the term.
+    ///
+    /// The expected type is propagated into the term when its type depends on the types of its
+    /// subterms:
+    /// - The body of an abstraction is checked against the return type of `expected`, and its
+    ///   parameter is bound to the argument type of `expected` if it does not have an annotation.
+    /// - Both branches of a conditional are checked against `expected`.
+    /// - The last term of a let binding or a sequence is checked against `expected`.
+    ///
+    /// Any other term has its type synthesized by `type_of` and a constraint stating that it must
+    /// be equal to `expected` is added.
+    ///
+    /// Propagating the expected type gives concrete types to parameters that would only be known
+    /// after unification otherwise, and it locates mismatches at the innermost term causing them.
+    fn check(&mut self, term: &Located<Term<'a>>, expected: &Ty) -> TyResult<Located<Ty>> {
+        let loc = term.loc;
+        let ty = match (&term.content, expected) {
+            (Term::Abs(name, ty, body), Ty::Arrow(arg_ty, ret_ty)) => {
+                self.add_constraint(arg_ty.as_ref().clone(), ty.clone(), loc);
+                let ty = match ty {
+                    Ty::Var(_) => arg_ty.as_ref().clone(),
+                    _ => ty.clone(),
+                };
+                self.inner.push(TyBinding { name: *name, ty });
+                let body_ty = self.check(body, ret_ty)?;
+                let bind = self.inner.pop().unwrap();
+                body_ty.map(|ty| Ty::Arrow(Box::new(bind.ty), Box::new(ty)))
+            }
+            (Term::Cond(t1, t2, t3), _) => {
+                let ty1 = self.type_of(t1)?;
+                self.add_constraint_with_hint(Ty::Bool, ty1.content, ty1.loc, Some(COND_HINT));
+                self.check(t2, expected)?;
+                self.check(t3, expected)?;
+                loc.with_content(expected.clone())
+            }
+            (Term::Let(kind, name, t1, t2), _) => {
+                self.bind_let(kind, name, t1)?;
+                let ty2 = self.check(t2, expected)?.content;
+                self.inner.pop().unwrap();
+                loc.with_content(ty2)
+            }
+            (Term::Seq(t1, t2), _) => {
+                let ty1 = self.type_of(t1)?;
+                self.add_constraint(Ty::Unit, ty1.content, ty1.loc);
+                self.check(t2, expected)?
+            }
+            _ => {
+                let ty = self.type_of(term)?;
+                self.add_constraint(expected.clone(), ty.content.clone(), ty.loc);
+                return Ok(ty);
+            }
+        };
+        self.annotate(term, &ty.content);
+        Ok(ty)
+    }
+
+    /// Annotates `term` with `ty` if annotations are enabled.
+    fn annotate(&mut self, term: &Located<Term<'a>>, ty: &Ty) {
+        if let Some(annotated) = &mut self.annotated {
+            let term = AnnotatedTerm::from_subterms(term, ty.clone(), annotated);
+            annotated.push(term);
         }
     }
 
@@ -218,11 +389,20 @@ impl<'a> Context<'a> {
     /// The type of a binary operation depends on its operator:
     /// - If it is an arithmetic operator, the operands must have type `Int`.
     /// - If it is a logic operator, the operands must have type `Bool`.
+    /// - If it is `BitAnd` or `BitOr`, the operands must have the same type, which can be either
+    ///   `Int` or `Bool`. Over `Int`s these operators are bitwise and over `Bool`s they are
+    ///   logical operators that always evaluate both operands.
     /// - If it is `Eq` or `Neq`, the operands must have the same type.
-    /// - If it is any other comparison operator, the operands must have type `Bool`.
+    /// - If it is any other comparison operator, the operands must have type `Int`.
     ///
     /// This rule adds one of the constraints stated above. The returned type is `Bool`, unless the
-    /// operation is an arithmetic operation, which has type `Int`.
+    /// operation is an arithmetic operation, which has type `Int`, or an overloaded operation,
+    /// which has the type of its operands. Whether the operands of overloaded operations are
+    /// `Int`s or `Bool`s is checked after unification.
+    ///
+    /// The operands of ordering comparisons are only constrained to be `Int`s after unification,
+    /// so operands whose types are resolved to `Unit` or functions are reported as not
+    /// comparable instead of as mismatches.
     fn type_of_binary_op(
         &mut self,
         loc: Location,
@@ -238,8 +418,7 @@ impl<'a> Context<'a> {
             | BinOp::Mul
             | BinOp::Div
             | BinOp::Rem
-            | BinOp::BitAnd
-            | BinOp::BitOr
+            | BinOp::Pow
             | BinOp::BitXor
             | BinOp::Shr
             | BinOp::Shl => {
@@ -247,14 +426,20 @@ impl<'a> Context<'a> {
                 self.add_constraint(Ty::Int, ty2.content, ty2.loc);
                 Ty::Int
             }
+            BinOp::BitAnd | BinOp::BitOr => {
+                self.add_constraint(ty1.content.clone(), ty2.content, ty2.loc);
+                self.overloaded_ops.push(ty1.clone());
+                ty1.content
+            }
             BinOp::Or | BinOp::And => {
-                self.add_constraint(Ty::Bool, ty1.content, ty1.loc);
-                self.add_constraint(Ty::Bool, ty2.content, ty2.loc);
+                let hint = Some(LOGIC_OP_HINT);
+                self.add_constraint_with_hint(Ty::Bool, ty1.content, ty1.loc, hint);
+                self.add_constraint_with_hint(Ty::Bool, ty2.content, ty2.loc, hint);
                 Ty::Bool
             }
             BinOp::Lt | BinOp::Gt | BinOp::Lte | BinOp::Gte => {
-                self.add_constraint(Ty::Int, ty1.content, ty1.loc);
-                self.add_constraint(Ty::Int, ty2.content, ty2.loc);
+                self.compared_ops.push(ty1);
+                self.compared_ops.push(ty2);
                 Ty::Bool
             }
             BinOp::Eq | BinOp::Neq => {
@@ -271,37 +456,68 @@ impl<'a> Context<'a> {
     ///
     /// This method introduces a new type variable `X` and adds the constraint `T1 = T2 -> X` where
     /// `T1` is `t1`'s type and `T2` is `t2`'s type. The returned type is `X`.
+    ///
+    /// Nested applications `(((f a1) a2) ... an)` are typed all at once. If the type of `f` is
+    /// already known to take less than `n` arguments and to return something that is not a
+    /// function, this method returns an error stating that there are too many arguments.
     fn type_of_app(
         &mut self,
         loc: Location,
         t1: &Located<Term<'a>>,
         t2: &Located<Term<'a>>,
     ) -> TyResult<Located<Ty>> {
-        let ty1 = self.type_of(t1)?.content;
-        let ty2 = self.type_of(t2)?;
-        let ty = self.new_ty();
+        let mut head = t1;
+        let mut args = vec![t2];
+        // The nested applications, from the outermost to the innermost one.
+        let mut apps = Vec::new();
+        while let Term::App(t1, t2) = &head.content {
+            apps.push(head);
+            head = t1.as_ref();
+            args.push(t2.as_ref());
+        }
 
-        self.add_constraint(
-            ty1,
-            Ty::Arrow(Box::new(ty2.content), Box::new(ty.clone())),
-            ty2.loc,
-        );
+        let mut ty = self.type_of(head)?.content;
+
+        let arity = ty.arity();
+        if arity < args.len() {
+            let mut ret_ty = &ty;
+            while let Some((_, ty2)) = ret_ty.as_arrow() {
+                ret_ty = ty2;
+            }
+            if !matches!(ret_ty, Ty::Var(_)) {
+                return Err(TyError::ArityMismatch {
+                    expected: arity,
+                    found: args.len(),
+                    loc,
+                });
+            }
+        }
+
+        let mut apps = apps.into_iter().rev();
+        for arg in args.into_iter().rev() {
+            let arg_ty = self.type_of(arg)?;
+            let ret_ty = self.new_ty();
+
+            self.add_constraint(
+                ty,
+                Ty::Arrow(Box::new(arg_ty.content), Box::new(ret_ty.clone())),
+                arg_ty.loc,
+            );
+
+            ty = ret_ty;
+            // Nested applications are not typed by `type_of`, so they are annotated here.
+            if let Some(app) = apps.next() {
+                self.annotate(app, &ty);
+            }
+        }
 
         Ok(loc.with_content(ty))
     }
 
     /// Returns the type of a let binding.
     ///
-    /// Typing a let binding requires adding a type binding for the name in the context. The name
-    /// is binded to whatever the type of the first term is. Then, the type of the let binding is
-    /// the same as the type of the second term.
-    ///
-    /// If the user provided a type annotation, the inferred type for the first name must coincide
-    /// with such annotation and a constraint is added accordingly.
-    ///
-    /// If the let binding is recursive. A type binding with the name and the type provided by the
-    /// annotation is added to the context before inferring any type in order to guarantee that the
-    /// name of the let binding will be in scope.
+    /// Typing a let binding requires adding a type binding for the name in the context with
+    /// `bind_let`. Then, the type of the let binding is the same as the type of the second term.
     ///
     /// Like when typing abstractions, the type binding added to the context must be removed to
     /// avoid leaking the binding to the outer scopes. This function returns an error if it is not
@@ -314,33 +530,58 @@ impl<'a> Context<'a> {
         t1: &Located<Term<'a>>,
         t2: &Located<Term<'a>>,
     ) -> TyResult<Located<Ty>> {
-        match kind {
-            LetKind::NonRec(opt_ty) => {
-                let ty1 = self.type_of(t1)?;
+        self.bind_let(kind, name, t1)?;
+        let ty2 = self.type_of(t2)?.content;
+        self.inner.pop().unwrap();
+        Ok(Located::new(ty2, loc))
+    }
 
-                if let Some(ty) = opt_ty {
-                    self.add_constraint(ty.content.clone(), ty1.content.clone(), ty1.loc);
-                }
+    /// Adds the type binding done by a let binding to the context.
+    ///
+    /// The name is binded to whatever the type of the first term is. If the user provided a type
+    /// annotation, the first term is checked against it instead.
+    ///
+    /// If the let binding is recursive. A type binding with the name and the type provided by the
+    /// annotation is added to the context before inferring any type in order to guarantee that the
+    /// name of the let binding will be in scope. If there is no annotation, the name is bound to a
+    /// new type variable instead, which must be resolved by unification.
+    fn bind_let(
+        &mut self,
+        kind: &LetKind,
+        name: &Located<Name<'a>>,
+        t1: &Located<Term<'a>>,
+    ) -> TyResult<()> {
+        match kind {
+            LetKind::NonRec(opt_ty) | LetKind::Strict(opt_ty) => {
+                let ty1 = match opt_ty {
+                    Some(ty) => self.check(t1, &ty.content)?,
+                    None => self.type_of(t1)?,
+                };
 
                 self.inner.push(TyBinding {
                     name: name.content,
                     ty: ty1.content,
                 });
             }
-            LetKind::Rec(ty) => {
+            LetKind::Rec(opt_ty) => {
+                let ty = match opt_ty {
+                    Some(ty) => ty.content.clone(),
+                    None => {
+                        let ty = self.new_ty();
+                        self.inferred_recs.push(name.loc.with_content(ty.clone()));
+                        ty
+                    }
+                };
+
                 self.inner.push(TyBinding {
                     name: name.content,
-                    ty: ty.content.clone(),
+                    ty: ty.clone(),
                 });
 
-                let found_ty = self.type_of(t1)?;
-                self.add_constraint(ty.content.clone(), found_ty.content, found_ty.loc);
+                self.check(t1, &ty)?;
             }
         };
-
-        let ty2 = self.type_of(t2)?.content;
-        self.inner.pop().unwrap();
-        Ok(Located::new(ty2, loc))
+        Ok(())
     }
 
     /// Returns the type of a conditional.
@@ -359,7 +600,7 @@ impl<'a> Context<'a> {
         let ty2 = self.type_of(t2)?.content;
         let ty3 = self.type_of(t3)?;
 
-        self.add_constraint(Ty::Bool, ty1.content, ty1.loc);
+        self.add_constraint_with_hint(Ty::Bool, ty1.content, ty1.loc, Some(COND_HINT));
         self.add_constraint(ty2.clone(), ty3.content, ty3.loc);
 
         Ok(loc.with_content(ty2))
@@ -385,17 +626,71 @@ impl<'a> Context<'a> {
 
     /// Returns the type of a primitive function.
     ///
-    /// The typing rules for each primitive are the following:
-    ///
-    /// - The `print` function has type `X -> Unit` for any `X`. Thus, a new variable is added to
-    /// the typing context to represent this `X`.
+    /// The type is the signature of the primitive returned by `prim_signature`. If the primitive
+    /// is polymorphic, a new variable is added to the typing context to replace its type
+    /// parameter. The argument type of every `print` is stored to annotate its output later.
     fn type_of_prim_fn(&mut self, loc: Location, prim: Primitive) -> TyResult<Located<Ty>> {
-        let ty = match prim {
-            Primitive::Print => {
-                let ty = self.new_ty();
-                Ty::Arrow(Box::new(ty), Box::new(Ty::Unit))
-            }
-        };
+        let mut ty = prim_signature(prim);
+        if !ty.is_concrete() {
+            let var = self.new_ty();
+            ty = ty.substitute(0, &var);
+        }
+        if let (Primitive::Print, Some((arg_ty, _))) = (prim, ty.as_arrow()) {
+            self.prints.push((loc, arg_ty.clone()));
+        }
         Ok(loc.with_content(ty))
     }
 }
+
+/// Returns the type signature of a primitive function.
+///
+/// Polymorphic primitives use `Ty::Var(0)` as their type parameter, which must be replaced by a
+/// new variable every time the primitive is used. The signatures are the following:
+///
+/// - The `print` function has type `X -> Unit` for any `X`.
+/// - The `neg` function has type `Int -> Int`.
+/// - The `not` function has type `Bool -> Bool`.
+/// - The `dbg` function has type `X -> X` for any `X`.
+/// - The `ignore` function has type `X -> Unit` for any `X`.
+/// - The `read_int` function has type `Unit -> Int`.
+pub fn prim_signature(prim: Primitive) -> Ty {
+    let arrow = |t1, t2| Ty::Arrow(Box::new(t1), Box::new(t2));
+    match prim {
+        Primitive::Print => arrow(Ty::Var(0), Ty::Unit),
+        Primitive::Neg => arrow(Ty::Int, Ty::Int),
+        Primitive::Not => arrow(Ty::Bool, Ty::Bool),
+        Primitive::Dbg => arrow(Ty::Var(0), Ty::Var(0)),
+        Primitive::Ignore => arrow(Ty::Var(0), Ty::Unit),
+        Primitive::ReadInt => arrow(Ty::Unit, Ty::Int),
+    }
+}
+
+/// Returns the index after the largest type variable in the type annotations of `term`, or zero
+/// if there are no type variables.
+fn vars_end(term: &Term<'_>) -> usize {
+    fn ty_vars_end(ty: &Ty) -> usize {
+        ty.free_vars()
+            .into_iter()
+            .max()
+            .map_or(0, |index| index + 1)
+    }
+
+    match term {
+        Term::Var(_) | Term::Lit(_) | Term::PrimFn(_) => 0,
+        Term::Abs(_, ty, body) => ty_vars_end(ty).max(vars_end(&body.content)),
+        Term::UnaryOp(_, t1) => vars_end(&t1.content),
+        Term::BinaryOp(_, t1, t2) | Term::App(t1, t2) | Term::Seq(t1, t2) => {
+            vars_end(&t1.content).max(vars_end(&t2.content))
+        }
+        Term::Cond(t1, t2, t3) => vars_end(&t1.content)
+            .max(vars_end(&t2.content))
+            .max(vars_end(&t3.content)),
+        Term::Let(kind, _, t1, t2) => {
+            let opt_ty = match kind {
+                LetKind::NonRec(opt_ty) | LetKind::Rec(opt_ty) | LetKind::Strict(opt_ty) => opt_ty,
+            };
+            let ty_end = opt_ty.as_ref().map_or(0, |ty| ty_vars_end(&ty.content));
+            ty_end.max(vars_end(&t1.content)).max(vars_end(&t2.content))
+        }
+    }
+}
@@ -15,11 +15,38 @@ pub type TyResult<T = Ty> = Result<T, TyError>;
 #[derive(Error, Debug, Eq, PartialEq)]
 pub enum TyError {
     /// Variant used when two types that should be equal are not.
-    #[error("Type mismatch: expected `{expected}`, found `{found}`")]
-    Mismatch { expected: Ty, found: Located<Ty> },
+    ///
+    /// The hint is a suggestion to fix common mistakes, like using an `Int` as a condition.
+    #[error(
+        "Type mismatch: expected `{expected}`, found `{found}`{}",
+        .hint.map(|hint| format!(" ({})", hint)).unwrap_or_default()
+    )]
+    Mismatch {
+        expected: Ty,
+        found: Located<Ty>,
+        hint: Option<&'static str>,
+    },
     /// Variant used when a name has not been binded to any type in the current scope.
     #[error("Name `{0}` is not bounded")]
     Unbounded(Located<String>),
+    /// Variant used when an ordering comparison is done between values of a type that cannot be
+    /// ordered.
+    #[error("Values of type `{0}` cannot be ordered")]
+    NotComparable(Located<Ty>),
+    /// Variant used when a type still has type variables after unification and strict
+    /// type-checking was requested.
+    #[error("Type `{0}` is ambiguous, consider adding type annotations")]
+    AmbiguousType(Located<Ty>),
+    /// Variant used when a function is called with more arguments than it can take.
+    #[error(
+        "Too many arguments: expected {expected} {}, found {found}",
+        if *.expected == 1 { "argument" } else { "arguments" }
+    )]
+    ArityMismatch {
+        expected: usize,
+        found: usize,
+        loc: Location,
+    },
 }
 
 impl TyError {
@@ -28,6 +55,8 @@ impl TyError {
         match self {
             TyError::Mismatch { found, .. } => found.loc,
             TyError::Unbounded(name) => name.loc,
+            TyError::NotComparable(ty) | TyError::AmbiguousType(ty) => ty.loc,
+            TyError::ArityMismatch { loc, .. } => *loc,
         }
     }
 }
@@ -8,12 +8,32 @@
 //! Pierce.
 use std::collections::VecDeque;
 
-use pijama_ast::location::Located;
+use pijama_ast::location::{Located, Location};
 
 use pijama_ty::Ty;
 
 use crate::{Context, TyError, TyResult};
 
+/// Unification of a single pair of types.
+pub trait UnifyWith {
+    /// Unifies this type with `other` and returns this type after applying the substitutions
+    /// found during unification.
+    ///
+    /// The location `loc` is used as the location of any returned error.
+    fn unify_with(&self, other: &Ty, loc: Location) -> TyResult<Ty>;
+}
+
+impl UnifyWith for Ty {
+    fn unify_with(&self, other: &Ty, loc: Location) -> TyResult<Ty> {
+        let mut ctx = Context::default();
+        ctx.add_constraint(self.clone(), other.clone(), loc);
+        let unif = Unifier::from_ctx(ctx)?;
+        let mut ty = self.clone();
+        unif.replace(&mut ty);
+        Ok(ty)
+    }
+}
+
 /// Solves the constraints created by the `Context` type.
 ///
 /// This type is able to find a set of `Substitution`s such that the program that produced the
@@ -32,12 +52,44 @@ impl Unifier {
     /// Consumes the constraints collected by the `Context` and then tries to unify those
     /// constraints using the `unify` method. If this process is successful, a new `Unifier` is
     /// returned ready to be used to replace type variables.
+    ///
+    /// The operands of ordering comparisons are constrained to be `Int`s once the rest of the
+    /// constraints are solved, unless they were resolved to `Unit` or functions, which cannot be
+    /// compared. The types of recursive bindings without a type annotation must be resolved
+    /// completely by unification. Otherwise, an error asking for an annotation is returned. The
+    /// operands of overloaded operators cannot be resolved to any type other than `Int` or
+    /// `Bool`.
     pub(super) fn from_ctx(ctx: Context) -> TyResult<Self> {
         let mut unif = Unifier {
             substitutions: Default::default(),
             constraints: ctx.constraints,
         };
         unif.unify()?;
+        for mut ty in ctx.compared_ops {
+            unif.replace(&mut ty.content);
+            if let Ty::Unit | Ty::Arrow(_, _) = ty.content {
+                return Err(TyError::NotComparable(ty));
+            }
+            let constr = Constraint::new(Ty::Int, ty.content);
+            unif.constraints.push_front(Located::new(constr, ty.loc));
+        }
+        unif.unify()?;
+        for mut ty in ctx.inferred_recs {
+            unif.replace(&mut ty.content);
+            if !ty.content.is_concrete() {
+                return Err(TyError::AmbiguousType(ty));
+            }
+        }
+        for mut ty in ctx.overloaded_ops {
+            unif.replace(&mut ty.content);
+            if let Ty::Unit | Ty::Arrow(_, _) = ty.content {
+                return Err(TyError::Mismatch {
+                    expected: Ty::Int,
+                    found: ty,
+                    hint: None,
+                });
+            }
+        }
         Ok(unif)
     }
 
@@ -56,7 +108,7 @@ impl Unifier {
     /// field.
     fn apply_substitution(&mut self, subst: &Substitution) {
         for constr in &mut self.constraints {
-            let Constraint { lhs, rhs } = &mut constr.content;
+            let Constraint { lhs, rhs, .. } = &mut constr.content;
             subst.apply(lhs);
             subst.apply(rhs);
         }
@@ -81,7 +133,7 @@ impl Unifier {
         // If there are constraints to be solved, take one.
         if let Some(constr) = self.constraints.pop_back() {
             let loc = constr.loc;
-            let Constraint { lhs, rhs } = constr.content;
+            let Constraint { lhs, rhs, hint } = constr.content;
 
             match (lhs, rhs) {
                 // If both sides of the constraint are equal, nothing needs to be done. We can skip
@@ -92,7 +144,7 @@ impl Unifier {
                 // right-hand side we replace the left-hand side type by the right-hand side in all
                 // the remaining constraints and add this substitution to our solution.
                 (Ty::Var(index), rhs) if !rhs.contains(index) => {
-                    let subst = Substitution::new(Ty::Var(index), rhs);
+                    let subst = Substitution::new(index, rhs);
                     self.apply_substitution(&subst);
                     self.unify()?;
                     self.add_substitution(subst);
@@ -102,7 +154,7 @@ impl Unifier {
                 // left-hand side we replace the right-hand side type by the left side-hand in all
                 // the remaining constraints and add this substitution to our solution.
                 (lhs, Ty::Var(index)) if !lhs.contains(index) => {
-                    let subst = Substitution::new(Ty::Var(index), lhs);
+                    let subst = Substitution::new(index, lhs);
                     self.apply_substitution(&subst);
                     self.unify()?;
                     self.add_substitution(subst);
@@ -121,9 +173,11 @@ impl Unifier {
 
                 // Otherwise, this constraint cannot be satisfied and we raise an error.
                 (lhs, rhs) => {
+                    let hint = hint.filter(|_| lhs == Ty::Bool && rhs == Ty::Int);
                     return Err(TyError::Mismatch {
                         expected: lhs,
                         found: Located::new(rhs, loc),
+                        hint,
                     });
                 }
             }
@@ -136,26 +190,22 @@ impl Unifier {
 /// Represents a substitution rule over types.
 #[derive(Debug)]
 struct Substitution {
-    /// Type to be replaced.
-    old: Ty,
+    /// Index of the type variable to be replaced.
+    index: usize,
     /// The replacement type.
     new: Ty,
 }
 
 impl Substitution {
     /// Creates a new substitution rule.
-    pub fn new(old: Ty, new: Ty) -> Self {
-        Substitution { old, new }
+    pub fn new(index: usize, new: Ty) -> Self {
+        Substitution { index, new }
     }
 
-    /// Applies the substitution rule over a type, replacing all occurrences of `old` by `new`.
+    /// Applies the substitution rule over a type, replacing all occurrences of the type variable
+    /// with index `index` by `new`.
     pub fn apply(&self, ty: &mut Ty) {
-        if *ty == self.old {
-            *ty = self.new.clone();
-        } else if let Ty::Arrow(ty1, ty2) = ty {
-            self.apply(ty1);
-            self.apply(ty2);
-        }
+        *ty = std::mem::replace(ty, Ty::Unit).substitute(self.index, &self.new);
     }
 }
 
@@ -170,11 +220,23 @@ pub struct Constraint {
     ///
     /// It usually represents the type found when creating a constraint.
     rhs: Ty,
+    /// Hint for the error returned if the constraint cannot be satisfied.
+    hint: Option<&'static str>,
 }
 
 impl Constraint {
     /// Creates a new constraint.
     pub fn new(lhs: Ty, rhs: Ty) -> Self {
-        Constraint { lhs, rhs }
+        Constraint {
+            lhs,
+            rhs,
+            hint: None,
+        }
+    }
+
+    /// Sets the hint of the constraint.
+    pub fn with_hint(mut self, hint: Option<&'static str>) -> Self {
+        self.hint = hint;
+        self
     }
 }